@@ -40,15 +40,32 @@ pub async fn help(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
                         vec![
                             ("join", "Joins a voice channel", true),
                             ("leave", "Leaves a music channel", true),
-                            ("play", "Play / queue a song from a YouTube URL", true),
+                            ("play", "Play / queue a song from a search, a YouTube, SoundCloud or Bandcamp URL, a link to an audio file, attached audio files or an attached M3U, JSON or text playlist. Playlist links take [start-end] --limit <n> --reverse --shuffle", true),
+                            ("playnext", "Queues a song or playlist right after the current song", true),
+                            ("playnow", "Plays a song or playlist right away, skipping the current song", true),
+                            ("search", "Shows the top YouTube results for a song name to pick one from", true),
                             ("stop", "Stops current playlist", true),
-                            ("skip", "Skips the current song", true),
+                            ("skip", "Skips the current song, or to a position with skip <n>. Votes unless you queued it or are a DJ", true),
+                            ("skipratio", "Shows or sets the share of listeners needed to vote skip", true),
+                            ("dj", "Shows or sets the DJ role and DJ-only mode", true),
                             ("pause", "Pauses the current song", true),
                             ("resume", "Resumes the current song", true),
+                            ("seek", "Jumps to a position in the current song", true),
+                            ("ff", "Fast forwards the current song", true),
+                            ("rewind", "Rewinds the current song", true),
+                            ("volume", "Shows or sets the volume (0 - 200)", true),
+                            ("loop", "Loops the current track, the queue or turns it off", true),
                             ("nowplaying", "Shows info about current song", true),
                             ("queue", "Show the current queue", true),
                             ("shuffle", "Shuffles the current playlist", true),
                             ("clear", "Clear the queue", true),
+                            ("remove", "Removes a song or a range of songs from the queue", true),
+                            ("move", "Moves a song to another position in the queue", true),
+                            ("history", "Shows recently played songs", true),
+                            ("previous", "Plays the previous song again", true),
+                            ("playlist", "Saves, loads, lists, shows or deletes playlists, add --guild to share one with the server", true),
+                            ("local", "Plays songs from the local music library, e.g. local artist:<artist> album:<album>", true),
+                            ("export", "Sends the queue or a saved playlist as an M3U, JSON or text file", true),
                         ]
                     },
 
@@ -3,8 +3,12 @@ use serenity::framework::standard::CommandResult;
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 
+use crate::player::permissions::{DJONLY_CHECK, DJ_CHECK};
+use crate::player::stop_queue;
+
 #[command]
 #[only_in(guilds)]
+#[checks(DjOnly, DJ)]
 async fn clear(ctx: &Context, msg: &Message) -> CommandResult {
     let guild = msg.guild(&ctx.cache).unwrap();
     let guild_id = guild.id;
@@ -16,8 +20,7 @@ async fn clear(ctx: &Context, msg: &Message) -> CommandResult {
 
     if let Some(handler_lock) = manager.get(guild_id) {
         let handler = handler_lock.lock().await;
-        let queue = handler.queue();
-        queue.stop();
+        stop_queue(&handler).await;
         msg.channel_id
             .send_message(&ctx.http, |m| {
                 m.embed(|e| {
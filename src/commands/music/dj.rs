@@ -0,0 +1,134 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use tracing::error;
+
+use crate::player::permissions::is_admin;
+use crate::settings::{guild_settings, update_guild_settings};
+
+#[command]
+#[only_in(guilds)]
+// Shows or changes the DJ role and DJ-only mode, changing them needs Manage Guild
+async fn dj(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild = msg.guild(&ctx.cache).unwrap();
+    let guild_id = guild.id;
+
+    let option = match args.single::<String>() {
+        Ok(option) => option.to_lowercase(),
+        Err(_) => {
+            let settings = guild_settings(&ctx.data, guild_id).await;
+            let role = match settings.dj_role {
+                Some(role_id) => role_id.mention().to_string(),
+                None => String::from("None"),
+            };
+            let mode = match settings.dj_only {
+                true => "On",
+                false => "Off",
+            };
+
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xffffff)
+                            .title(":headphones: DJ settings")
+                            .fields(vec![
+                                ("DJ role", role, true),
+                                ("DJ-only mode", mode.to_string(), true),
+                            ])
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if !is_admin(ctx, &guild, msg.author.id).await {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: You need the Manage Server permission to change DJ settings.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let value = args.rest().trim().to_string();
+
+    let change = match option.as_str() {
+        "role" => match value.to_lowercase().as_str() {
+            "none" | "off" => Some(Change::Role(None)),
+            _ => value
+                .parse::<RoleId>()
+                .ok()
+                .or_else(|| guild.role_by_name(&value).map(|role| role.id))
+                .filter(|role_id| guild.roles.contains_key(role_id))
+                .map(|role_id| Change::Role(Some(role_id))),
+        },
+        "only" => match value.to_lowercase().as_str() {
+            "on" => Some(Change::Only(true)),
+            "off" => Some(Change::Only(false)),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    let change = match change {
+        Some(change) => change,
+        None => {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(":warning: Use the command like this: dj role <role | none> or dj only <on | off>")
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if let Err(why) = update_guild_settings(&ctx.data, guild_id, |s| match change {
+        Change::Role(dj_role) => s.dj_role = dj_role,
+        Change::Only(dj_only) => s.dj_only = dj_only,
+    })
+    .await
+    {
+        error!("Failed to save settings: {:?}", why);
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title("Failed to save DJ settings.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let title = match change {
+        Change::Role(Some(_)) => ":headphones: DJ role set!",
+        Change::Role(None) => ":headphones: DJ role removed!",
+        Change::Only(true) => ":lock: DJ-only mode turned on!",
+        Change::Only(false) => ":unlock: DJ-only mode turned off!",
+    };
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| e.colour(0xffffff).title(title).timestamp(Timestamp::now()))
+        })
+        .await?;
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum Change {
+    Role(Option<RoleId>),
+    Only(bool),
+}
@@ -0,0 +1,104 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use std::borrow::Cow;
+
+use crate::playlist_files::{self, Format};
+use crate::playlists::{find_playlist, PlaylistTrack};
+
+#[command]
+#[only_in(guilds)]
+// Sends the queue or a saved playlist as a file, M3U unless another format is given
+async fn export(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let format = match args.current().and_then(Format::from_name) {
+        Some(format) => {
+            args.advance();
+            format
+        }
+        None => Format::M3u,
+    };
+    let name = args.rest().trim();
+
+    let (name, tracks) = match name.is_empty() {
+        true => {
+            let manager = songbird::get(ctx)
+                .await
+                .expect("Songbird Voice client placed in at initialisation.")
+                .clone();
+
+            let queue = match manager.get(guild_id) {
+                Some(handler_lock) => handler_lock.lock().await.queue().current_queue(),
+                None => Vec::new(),
+            };
+
+            let tracks = queue
+                .iter()
+                .filter_map(PlaylistTrack::from_track)
+                .collect::<Vec<_>>();
+            (String::from("queue"), tracks)
+        }
+        false => match find_playlist(&ctx.data, msg.author.id, guild_id, name).await {
+            Some((_, playlist)) => (playlist.name, playlist.tracks),
+            None => {
+                msg.channel_id
+                    .send_message(&ctx.http, |m| {
+                        m.embed(|e| {
+                            e.colour(0xf38ba8)
+                                .title(format!(":warning: There's no playlist called {}.", name))
+                                .timestamp(Timestamp::now())
+                        })
+                    })
+                    .await?;
+                return Ok(());
+            }
+        },
+    };
+
+    if tracks.is_empty() {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: There's nothing to export.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let raw = playlist_files::write(&name, &tracks, format);
+    let filename = format!("{}.{}", file_stem(&name), format.extension());
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.add_file(AttachmentType::Bytes {
+                data: Cow::from(raw.into_bytes()),
+                filename,
+            })
+            .embed(|e| {
+                e.colour(0xffffff)
+                    .title(format!(":outbox_tray: Exported {}!", name))
+                    .description(format!(
+                        "{} songs, play the file again by attaching it to play.",
+                        tracks.len()
+                    ))
+                    .timestamp(Timestamp::now())
+            })
+        })
+        .await?;
+    Ok(())
+}
+
+// Playlist names can hold anything, file names shouldn't
+fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| match c.is_alphanumeric() || c == '-' || c == '_' {
+            true => c,
+            false => '_',
+        })
+        .collect()
+}
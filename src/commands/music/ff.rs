@@ -0,0 +1,13 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::commands::music::seek::seek_relative;
+
+#[command]
+#[aliases("forward")]
+#[only_in(guilds)]
+async fn ff(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    seek_relative(ctx, msg, args, true).await
+}
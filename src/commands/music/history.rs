@@ -0,0 +1,67 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::CommandResult;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::commands::utils::is_link;
+use crate::player::state::with_guild_state;
+
+#[command]
+#[aliases("played")]
+#[only_in(guilds)]
+async fn history(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild = msg.guild(&ctx.cache).unwrap();
+    let guild_id = guild.id;
+
+    let history = with_guild_state(&ctx.data, guild_id, |state| state.history.clone()).await;
+
+    if history.is_empty() {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: Nothing has been played yet.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let mut desc = String::from("+ - + - + - + - + - + - + - + - + - +\n");
+    for (i, entry) in history.iter().enumerate() {
+        let title = match entry.source_url.as_deref().filter(|source| is_link(source)) {
+            Some(url) => format!("[{}]({})", entry.title, url),
+            None => entry.title.clone(),
+        };
+        let requester = match entry.requester {
+            Some(user_id) => format!(" - {}", user_id.mention()),
+            None => String::new(),
+        };
+        let line = format!(
+            "{}. {}{} <t:{}:R>\n",
+            i + 1,
+            title,
+            requester,
+            entry.played_at.unix_timestamp()
+        );
+
+        // embed descriptions are capped at 4096 characters
+        if desc.len() + line.len() > 4000 {
+            break;
+        }
+        desc.push_str(&line);
+    }
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(0xffffff)
+                    .title(":clock3: - History - :clock3:")
+                    .description(desc)
+                    .timestamp(Timestamp::now())
+            })
+        })
+        .await?;
+    Ok(())
+}
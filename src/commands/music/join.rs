@@ -3,6 +3,10 @@ use serenity::framework::standard::CommandResult;
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 
+use crate::player::events::register_events;
+use crate::player::idle::refresh_idle;
+use crate::player::persist::restore_queue;
+
 #[command]
 #[only_in(guilds)]
 // Joins voice channel, mostly not needed because !play tries to join vc, used in case
@@ -52,7 +56,7 @@ async fn join(ctx: &Context, msg: &Message) -> CommandResult {
             .expect("Songbird Voice client placed in at initialisation.")
             .clone();
 
-        let (_, success) = manager.join(guild_id, connect_to).await;
+        let (handler_lock, success) = manager.join(guild_id, connect_to).await;
 
         if let Err(_channel) = success {
             msg.channel_id
@@ -67,6 +71,17 @@ async fn join(ctx: &Context, msg: &Message) -> CommandResult {
                 .await?;
             return Ok(());
         }
+
+        {
+            let mut handler = handler_lock.lock().await;
+            register_events(ctx, manager.clone(), guild_id, &mut handler);
+        }
+
+        // Bring back whatever was queued before the bot restarted
+        restore_queue(ctx, guild_id).await;
+
+        // Nothing is queued yet, don't hang around forever if nobody plays anything
+        refresh_idle(ctx, guild_id, &[]).await;
     }
     msg.channel_id
         .send_message(&ctx.http, |m| {
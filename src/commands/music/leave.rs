@@ -3,8 +3,12 @@ use serenity::framework::standard::CommandResult;
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 
+use crate::player::permissions::DJ_CHECK;
+use crate::player::persist::forget_queue;
+
 #[command]
 #[only_in(guilds)]
+#[checks(DJ)]
 pub async fn leave(ctx: &Context, msg: &Message) -> CommandResult {
     let guild = msg.guild(&ctx.cache).unwrap();
     let guild_id = guild.id;
@@ -30,6 +34,9 @@ pub async fn leave(ctx: &Context, msg: &Message) -> CommandResult {
                 .await?;
         }
 
+        // Leaving on purpose means the queue is done, don't bring it back later
+        forget_queue(guild_id).await;
+
         msg.channel_id
             .send_message(&ctx.http, |m| {
                 m.embed(|e| {
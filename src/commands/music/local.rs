@@ -0,0 +1,188 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use tracing::error;
+
+use crate::commands::utils::to_time;
+use crate::library::{music_dir, rescan, LibraryContainer, Query};
+use crate::player::permissions::{can_queue, is_dj};
+use crate::player::{enqueue, join_author};
+use crate::settings::guild_settings;
+
+// Most songs a single local search puts in the queue
+const LOCAL_LIMIT: usize = 50;
+
+#[command]
+#[only_in(guilds)]
+// Plays songs from the local music library, local rescan reads the directory again
+async fn local(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let guild = msg.guild(&ctx.cache).unwrap();
+    let guild_id = guild.id;
+
+    if music_dir().is_none() {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: There's no local music library.")
+                        .description("Set MUSIC_DIR to a directory with music to use one.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let input = args.rest().trim();
+
+    if input.eq_ignore_ascii_case("rescan") {
+        if !is_dj(ctx, &guild, msg.author.id).await {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(":warning: Only DJs can rescan the music library.")
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+
+        let count = rescan(&ctx.data).await.unwrap_or(0);
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xffffff)
+                        .title(":file_cabinet: Rescanned the music library!")
+                        .description(format!("{} songs found.", count))
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let query = Query::parse(input);
+    if query.is_empty() {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: Use the command like this: local <song name> or local artist:<artist> album:<album>")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    if !can_queue(ctx, &guild, msg.author.id).await {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: The queue is locked to DJs right now.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let matches = {
+        let data = ctx.data.read().await;
+        let library = data
+            .get::<LibraryContainer>()
+            .expect("Library placed in at initialisation.")
+            .clone();
+        let matches = library.read().await.search(&query);
+        matches
+    };
+
+    if matches.is_empty() {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: Nothing in the music library matches that.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let handler_lock = match join_author(ctx, msg, &guild).await? {
+        Some(handler_lock) => handler_lock,
+        None => return Ok(()),
+    };
+
+    let settings = guild_settings(&ctx.data, guild_id).await;
+
+    let mut desc = String::new();
+    let mut queued = 0;
+    let mut errors = 0;
+    let mut total_time = 0;
+    for track in matches.iter().take(LOCAL_LIMIT) {
+        let source = match track.descriptor().input().await {
+            Ok(source) => source,
+            Err(why) => {
+                error!(
+                    "Err starting source for {}: {:?}",
+                    track.path.display(),
+                    why
+                );
+                errors += 1;
+                continue;
+            }
+        };
+
+        let mut handler = handler_lock.lock().await;
+        enqueue(&mut handler, source, &settings, msg.author.id).await;
+        queued += 1;
+        total_time += track.duration.map(|d| d.as_secs()).unwrap_or(0);
+
+        let line = format!(
+            "{} - {}\n",
+            track.title,
+            track.artist.as_deref().unwrap_or("Unknown")
+        );
+        // embed descriptions are capped at 4096 characters
+        if desc.len() + line.len() <= 4000 {
+            desc.push_str(&line);
+        }
+    }
+
+    let title = match queued {
+        1 => String::from(":notes: Song added to the queue!"),
+        n => format!(":notes: {} songs added to the queue!", n),
+    };
+    let mut skipped = Vec::new();
+    if matches.len() > LOCAL_LIMIT {
+        skipped.push(format!("{} more matched", matches.len() - LOCAL_LIMIT));
+    }
+    if errors > 0 {
+        skipped.push(format!("{} couldn't be played", errors));
+    }
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(0xffffff)
+                    .title(title)
+                    .description(desc)
+                    .fields(vec![
+                        ("Songs queued", queued.to_string(), true),
+                        ("Playtime", to_time(total_time), true),
+                    ]);
+                if !skipped.is_empty() {
+                    e.footer(|f| f.text(skipped.join(", ")));
+                }
+                e.timestamp(Timestamp::now())
+            })
+        })
+        .await?;
+    Ok(())
+}
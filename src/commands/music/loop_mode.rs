@@ -0,0 +1,95 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use tracing::error;
+
+use crate::player::permissions::DJONLY_CHECK;
+use crate::settings::{guild_settings, update_guild_settings, LoopMode};
+
+#[command("loop")]
+#[aliases("repeat")]
+#[only_in(guilds)]
+#[checks(DjOnly)]
+async fn loop_mode(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild = msg.guild(&ctx.cache).unwrap();
+    let guild_id = guild.id;
+
+    // Without an argument just show the current mode
+    if args.is_empty() {
+        let settings = guild_settings(&ctx.data, guild_id).await;
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xffffff)
+                        .title(format!(":repeat: Loop mode is {}", settings.loop_mode))
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let mode = match args.single::<String>()?.to_lowercase().as_str() {
+        "track" | "song" => LoopMode::Track,
+        "queue" | "all" => LoopMode::Queue,
+        "off" | "none" => LoopMode::Off,
+        _ => {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(
+                                ":warning: Use the command like this: loop <track | queue | off>",
+                            )
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if let Err(why) = update_guild_settings(&ctx.data, guild_id, |s| s.loop_mode = mode).await {
+        error!("Failed to save settings: {:?}", why);
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title("Failed to save loop mode.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let manager = songbird::get(ctx)
+        .await
+        .expect("Songbird Voice client placed in at initialisation.")
+        .clone();
+
+    // Later tracks are handled by the track events, only the current one needs a nudge
+    if let Some(handler_lock) = manager.get(guild_id) {
+        let handler = handler_lock.lock().await;
+        if let Some(current) = handler.queue().current() {
+            let _ = match mode {
+                LoopMode::Track => current.enable_loop(),
+                _ => current.disable_loop(),
+            };
+        }
+    }
+
+    let title = match mode {
+        LoopMode::Track => ":repeat_one: Looping the current track!",
+        LoopMode::Queue => ":repeat: Looping the queue!",
+        LoopMode::Off => ":arrow_right: Loop turned off!",
+    };
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| e.colour(0xffffff).title(title).timestamp(Timestamp::now()))
+        })
+        .await?;
+    Ok(())
+}
@@ -1,11 +1,28 @@
 pub mod clear;
+pub mod dj;
+pub mod export;
+pub mod ff;
+pub mod history;
 pub mod join;
 pub mod leave;
+pub mod local;
+pub mod loop_mode;
+pub mod move_track;
 pub mod nowplaying;
 pub mod pause;
 pub mod play;
+pub mod playlist;
+pub mod playnext;
+pub mod playnow;
+pub mod previous;
 pub mod queue;
+pub mod remove;
 pub mod resume;
+pub mod rewind;
+pub mod search;
+pub mod seek;
 pub mod shuffle;
 pub mod skip;
+pub mod skip_ratio;
 pub mod stop;
+pub mod volume;
@@ -0,0 +1,111 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::player::permissions::{is_dj, requested_all, DJONLY_CHECK};
+
+#[command("move")]
+#[aliases("mv")]
+#[only_in(guilds)]
+#[checks(DjOnly)]
+async fn move_track(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let (from, to) = match (args.single::<usize>(), args.single::<usize>()) {
+        (Ok(from), Ok(to)) => (from, to),
+        _ => {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(":warning: Use the command like this: move <from> <to>")
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let guild = msg.guild(&ctx.cache).unwrap();
+    let guild_id = guild.id;
+
+    let manager = songbird::get(ctx)
+        .await
+        .expect("Songbird Voice client placed in at initialisation.")
+        .clone();
+
+    if let Some(handler_lock) = manager.get(guild_id) {
+        let handler = handler_lock.lock().await;
+        let queue = handler.queue();
+        let len = queue.len();
+
+        // 1 is the track that is playing right now, it has to stay in front
+        if from < 2 || to < 2 || from > len || to > len {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(":warning: Invalid position.")
+                            .description(format!(
+                                "Pick positions between 2 and {}, the current song can't be moved.",
+                                len
+                            ))
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+
+        // Besides DJs, people can only move songs they queued themselves
+        let tracks = queue.current_queue();
+        if !requested_all(&tracks[from - 1..from], msg.author.id).await
+            && !is_dj(ctx, &guild, msg.author.id).await
+        {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(":warning: You can only move songs you queued yourself.")
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+
+        let title = queue.modify_queue(|queue| {
+            let track = queue.remove(from - 1)?;
+            let title = track.metadata().title.clone();
+            queue.insert(to - 1, track);
+            title
+        });
+
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xffffff)
+                        .title(":arrow_up_down: Moved song in the queue")
+                        .description(format!(
+                            "{} - {} → {}",
+                            title.unwrap_or_else(|| String::from("Unknown")),
+                            from,
+                            to
+                        ))
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+    } else {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: Not in a voice channel.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+    }
+    Ok(())
+}
@@ -3,7 +3,8 @@ use serenity::framework::standard::CommandResult;
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 
-use crate::commands::utils::to_time;
+use crate::commands::utils::{is_link, to_position};
+use crate::settings::guild_settings;
 
 #[command]
 #[aliases("np")]
@@ -48,28 +49,29 @@ async fn nowplaying(ctx: &Context, msg: &Message) -> CommandResult {
             None => String::from("Unknown"),
         };
 
-        let time_formatted = {
-            format!(
-                "{} - {}",
-                to_time(track_info.position.as_secs()),
-                to_time(metadata.duration.unwrap().as_secs())
-            )
-        };
+        let time_formatted = to_position(track_info.position, metadata.duration);
+        let settings = guild_settings(&ctx.data, guild_id).await;
+
+        let link = metadata.source_url.clone().filter(|source| is_link(source));
 
         msg.channel_id.send_message(&ctx.http, |m| {
-            m.embed(|e| e
-                .colour(0xffffff)
-                .title(metadata.title.clone().unwrap_or_else(|| String::from("Unknown")))
-                .thumbnail(metadata.thumbnail.clone().unwrap_or_else(|| String::from("https://images.unsplash.com/photo-1611162616475-46b635cb6868?ixlib=rb-4.0.3")))
-                .url(metadata.source_url.clone().unwrap())
-                .fields(vec![
-                    ("Artist", metadata.artist.clone().unwrap_or_else(|| String::from("Unknown")), false),
-                    ("Released", date_formatted, true),
-                    ("Position", time_formatted, true),
-                    ("Status", format!("{:?}", track_info.playing), true),
-                ])
-                .timestamp(Timestamp::now())
-            )
+            m.embed(|e| {
+                e.colour(0xffffff)
+                    .title(metadata.title.clone().unwrap_or_else(|| String::from("Unknown")))
+                    .thumbnail(metadata.thumbnail.clone().unwrap_or_else(|| String::from("https://images.unsplash.com/photo-1611162616475-46b635cb6868?ixlib=rb-4.0.3")))
+                    .fields(vec![
+                        ("Artist", metadata.artist.clone().unwrap_or_else(|| String::from("Unknown")), false),
+                        ("Released", date_formatted, true),
+                        ("Position", time_formatted, true),
+                        ("Status", format!("{:?}", track_info.playing), true),
+                        ("Loop", settings.loop_mode.to_string(), true),
+                    ])
+                    .timestamp(Timestamp::now());
+                if let Some(link) = link {
+                    e.url(link);
+                }
+                e
+            })
         }).await?;
     } else {
         msg.channel_id
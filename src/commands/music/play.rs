@@ -1,29 +1,139 @@
-use regex::Regex;
+use rand::seq::SliceRandom;
 use serenity::framework::standard::macros::command;
 use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::prelude::*;
 use serenity::prelude::*;
-use songbird::input::Restartable;
 use songbird::tracks::TrackHandle;
-use std::collections::HashMap;
-use tokio::process::Command;
-use tokio::sync::mpsc;
-use tracing::{error, info};
+use songbird::Call;
+use std::borrow::Cow;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::time::interval;
+use tracing::error;
 
 use crate::commands::utils::to_time;
+use crate::player::events::PRELOAD_TRACKS;
+use crate::player::permissions::{is_dj, DJONLY_CHECK};
+use crate::player::{enqueue, join_author, queue_next, total_time, RequestedBy};
+use crate::playlist_files::{self, Format};
+use crate::settings::{guild_settings, GuildSettings};
+use crate::sources::http::has_media_extension;
+use crate::sources::{resolve, web_url, Source, TrackDescriptor};
+
+// How often the progress of a queuing playlist gets updated
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(3);
+
+// Shown when playnow found someone else's track playing by the time it was ready
+const KEPT_CURRENT: &str = "The current song isn't yours anymore, so this plays next instead";
+
+// Failures past this many get sent as a file instead
+const REPORT_LINES: usize = 10;
 
 #[command]
 #[aliases(p)]
 #[only_in(guilds)]
+#[checks(DjOnly)]
 async fn play(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let url = match args.clone().single::<String>() {
-        Ok(url) => url.clone(),
-        Err(_) => {
+    // A playlist file attached to the message is played instead of the arguments
+    if let Some((attachment, format)) = msg.attachments.iter().find_map(|attachment| {
+        Format::from_filename(&attachment.filename).map(|format| (attachment, format))
+    }) {
+        return play_file(ctx, msg, attachment, format).await;
+    }
+
+    // Audio and video files shared in the chat play as they are
+    let media = msg
+        .attachments
+        .iter()
+        .filter(|attachment| is_media(attachment))
+        .collect::<Vec<_>>();
+    if args.is_empty() && !media.is_empty() {
+        return play_attachments(ctx, msg, &media).await;
+    }
+
+    play_query(ctx, msg, args.rest(), Placement::Back).await
+}
+
+// Where newly queued tracks end up
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Placement {
+    // Behind everything that's already queued
+    Back,
+    // Right after the current track
+    Next,
+    // Right after the current track, which gets skipped
+    Now,
+}
+
+impl Placement {
+    fn command(&self) -> &'static str {
+        match self {
+            Placement::Back => "play",
+            Placement::Next => "playnext",
+            Placement::Now => "playnow",
+        }
+    }
+}
+
+// Resolves a link or search and queues what it finds, play, playnext and playnow
+// all go through here
+pub async fn play_query(
+    ctx: &Context,
+    msg: &Message,
+    query: &str,
+    placement: Placement,
+) -> CommandResult {
+    let query = query.trim();
+    if query.is_empty() {
+        let usage = match placement {
+            Placement::Back => String::from("play <url> or <song name>, or attach audio files"),
+            _ => format!("{} <url> or <song name>", placement.command()),
+        };
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(format!(":warning: Use the command like this: {usage}"))
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    // Links can be followed by which part of the playlist to queue
+    let mut words = query.split_whitespace();
+    let first = words.next().unwrap_or_default();
+    let (query, options) = match web_url(first) {
+        Some(_) => (first, words.collect::<Vec<_>>()),
+        None => (query, Vec::new()),
+    };
+
+    let guild = msg.guild(&ctx.cache).unwrap();
+    let guild_id = guild.id;
+
+    let settings = guild_settings(&ctx.data, guild_id).await;
+    let requester = msg.author.id;
+
+    // A seperate !join is inconvenient, so bot joins with !play if not in voice channel
+    let handler_lock = match join_author(ctx, msg, &guild).await? {
+        Some(handler_lock) => handler_lock,
+        None => return Ok(()),
+    };
+
+    let tracks = match resolve(query).await {
+        Ok(tracks) => tracks,
+        Err(why) => {
+            error!("Err resolving '{}': {:?}", query, why);
+
             msg.channel_id
                 .send_message(&ctx.http, |m| {
                     m.embed(|e| {
                         e.colour(0xf38ba8)
-                            .title(":warning: Use the command like this: play <url> or <song name>")
+                            .title(":warning: Error adding song to playlist.")
+                            .description(why.to_string())
                             .timestamp(Timestamp::now())
                     })
                 })
@@ -32,376 +142,670 @@ async fn play(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
         }
     };
 
-    let search = args.clone();
-
-    let guild = msg.guild(&ctx.cache).unwrap();
-    let guild_id = guild.id;
-
-    let mut tracks_to_remove = 1;
-
-    let manager = songbird::get(ctx)
-        .await
-        .expect("Songbird Voice client placed in at initialisation.")
-        .clone();
-
-    // A seperate !join is inconvenient, so bot joins with !play if not in voice channel
-    if manager.get(guild_id).is_none() {
-        let channel_id = guild
-            .voice_states
-            .get(&msg.author.id)
-            .and_then(|voice_state| voice_state.channel_id);
-
-        let connect_to = match channel_id {
-            Some(channel) => channel,
+    // Only playlists have parts to pick from, a single song ignores the options
+    let mut tracks = match tracks.len() {
+        0 | 1 => tracks,
+        _ => match Selection::parse(&options) {
+            Some(selection) => selection.apply(tracks),
             None => {
                 msg.channel_id
                     .send_message(&ctx.http, |m| {
                         m.embed(|e| {
                             e.colour(0xf38ba8)
-                                .title(":warning: Join a voice channel first!")
+                                .title(format!(
+                                    ":warning: Use the command like this: {} <playlist url> [start-end] --limit <n> --reverse --shuffle",
+                                    placement.command()
+                                ))
                                 .timestamp(Timestamp::now())
                         })
                     })
                     .await?;
-
                 return Ok(());
             }
-        };
+        },
+    };
 
-        let manager = songbird::get(ctx)
-            .await
-            .expect("Songbird Voice client placed in at initialisation.")
-            .clone();
+    if tracks.is_empty() {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: There's nothing in that part of the playlist.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
 
-        let (_, success) = manager.join(guild_id, connect_to).await;
+    // Playlists are queued in the background
+    if tracks.len() > 1 {
+        return queue_tracks(
+            ctx,
+            msg,
+            handler_lock,
+            tracks,
+            &settings,
+            requester,
+            placement,
+        )
+        .await;
+    }
 
-        if let Err(_channel) = success {
-            msg.channel_id
-                .send_message(&ctx.http, |m| {
-                    m.embed(|e| {
-                        e.colour(0xf38ba8)
-                            .title(":warning: error joining channel.")
-                            .description("Please ensure I have the correct permissions.")
-                            .timestamp(Timestamp::now())
-                    })
-                })
-                .await?;
-        }
+    let track = tracks.remove(0);
+    queue_track(
+        ctx,
+        msg,
+        handler_lock,
+        track,
+        &settings,
+        requester,
+        placement,
+    )
+    .await
+}
+
+// Puts a track that was just queued where it was asked for and returns where it
+// ended up. Only the first of several tracks played now skips the current one, the
+// rest line up behind it.
+async fn place(
+    handler: &Call,
+    track: &TrackHandle,
+    placement: Placement,
+    after: Option<&TrackHandle>,
+    requester: UserId,
+    dj: bool,
+) -> Placement {
+    let mut placed = placement;
+    if placement != Placement::Back {
+        queue_next(handler, track, after);
     }
-    if let Some(handler_lock) = manager.get(guild_id) {
-        // Handle YT Music by redirecting to youtube.com equivalent
-        if url.clone().starts_with("http") && url.contains("music.") {
-            let _ = url.replace("music.", "");
+
+    let current = handler
+        .queue()
+        .current()
+        .filter(|current| current.uuid() != track.uuid());
+    if let (Placement::Now, None, Some(current)) = (placement, after, current) {
+        // Another track may have started while this one was being fetched, so
+        // whose it is gets checked again
+        let owner = current.typemap().read().await.get::<RequestedBy>().copied();
+        if dj || owner == Some(requester) {
+            let _ = handler.queue().skip();
+        } else {
+            placed = Placement::Next;
         }
+    }
 
-        // search on youtube for video with given name and pick first from search result
-        if !url.clone().starts_with("http") {
-            let mut handler = handler_lock.lock().await;
-            let source = match songbird::input::ytdl_search(search.message()).await {
-                Ok(source) => source,
-                Err(why) => {
-                    println!("Err starting source: {:?}", why);
-
-                    msg.channel_id
-                        .send_message(&ctx.http, |m| {
-                            m.embed(|e| {
-                                e.colour(0xf38ba8)
-                                    .title(":warning: Error adding song to playlist")
-                                    .description("This could mean that one of the songs in the playlist is unavailable.")
-                                    .timestamp(Timestamp::now())
-                            })
-                        })
-                        .await?;
-                    return Ok(());
-                }
-            };
-
-            let song = handler.enqueue_source(source);
-            let mut i = 0;
-            for queued_song in handler.queue().current_queue() {
-                if let Some(duration) = queued_song.metadata().duration {
-                    i += duration.as_secs()
-                } else {
-                    i += 0;
-                }
-            }
+    // Tracks landing in the first few slots missed the preload of the current one
+    let preloaded = handler
+        .queue()
+        .current_queue()
+        .iter()
+        .skip(1)
+        .take(PRELOAD_TRACKS)
+        .any(|queued| queued.uuid() == track.uuid());
+    if preloaded {
+        let _ = track.make_playable();
+    }
 
-            let playtime = to_time(i);
-            let metadata = song.metadata();
+    placed
+}
 
-            msg.channel_id
-                .send_message(&ctx.http, |m| {
-                    m.embed(|e| {
-                        e.colour(0xffffff)
-                            .title(":notes: Song added to the queue!")
-                            .thumbnail(metadata.thumbnail.clone().unwrap_or_else(|| String::from("https://images.unsplash.com/photo-1611162616475-46b635cb6868?ixlib=rb-4.0.3")))
-                            .description(format!(
-                                "{} - {}",
-                                metadata.title.clone().unwrap(),
-                                metadata.artist.clone().unwrap()
-                            ))
-                            .fields(vec![
-                                ("Songs queued", format!("{}", handler.queue().len()), true),
-                                ("Total playtime", playtime, true)
-                            ])
-                            .timestamp(Timestamp::now())
-                    })
-                })
-                .await?;
-            return Ok(());
-        // handle playlist
-        } else if url.contains("playlist") {
-            let mut handler = handler_lock.lock().await;
-            // goal is to immediately queue and start playing first track while processing whole queue
-            if handler.queue().current().is_none() {
-                info!("Current queue is empty, launching first track");
-                let get_raw_list = Command::new("yt-dlp")
-                    .args(["-j", "--flat-playlist", &url])
-                    .output()
-                    .await;
-
-                let raw_list = match get_raw_list {
-                    Ok(list) => String::from_utf8(list.stdout).unwrap(),
-                    Err(_) => String::from("Error!"),
-                };
-
-                let re =
-                    Regex::new(r#""url": "(https://www.youtube.com/watch\?v=[A-Za-z0-9]{11})""#)
-                        .unwrap();
-
-                let mut urls: Vec<String> = re
-                    .captures_iter(&raw_list)
-                    .map(|cap| cap[1].to_string())
-                    .collect();
-                
-                let clone_urls = urls.clone();
-                for url in clone_urls {
-                    info!("Queueing --> {}", url);
-                    match Restartable::ytdl(url, true).await {
-                        Ok(source) => {
-                            handler.enqueue_source(source.into());
-                            urls.remove(0);
-                            break;
-                        }
-                        Err(why) => {
-                            error!("Err starting source: {:?}", why);
-                            urls.remove(0);
-                            tracks_to_remove += 1;
-                            continue;
-                        }
+// Whether the requester may skip any track when playing now, not just their own.
+// Looked up before the handler is locked, since it can take a request to Discord.
+async fn skips_any(ctx: &Context, msg: &Message, requester: UserId, placement: Placement) -> bool {
+    if placement != Placement::Now {
+        return false;
+    }
+    match msg.guild(&ctx.cache) {
+        Some(guild) => is_dj(ctx, &guild, requester).await,
+        None => false,
+    }
+}
+
+// Which part of a playlist gets queued and in what order, from
+// play <url> [start-end] --limit <n> --reverse --shuffle
+#[derive(Debug, Default)]
+struct Selection {
+    // How many tracks to skip, start counts from 1
+    skip: usize,
+    end: Option<usize>,
+    limit: Option<usize>,
+    reverse: bool,
+    shuffle: bool,
+}
+
+impl Selection {
+    // None when one of the options makes no sense
+    fn parse(words: &[&str]) -> Option<Self> {
+        let mut selection = Selection::default();
+        let mut words = words.iter();
+        while let Some(word) = words.next() {
+            match *word {
+                "--limit" | "-l" => {
+                    let limit = words.next()?.parse::<usize>().ok();
+                    selection.limit = Some(limit.filter(|limit| *limit > 0)?);
+                }
+                "--reverse" | "-r" => selection.reverse = true,
+                "--shuffle" | "-s" => selection.shuffle = true,
+                // 5-20, or 5- for everything from the fifth track on
+                range => {
+                    let (start, end) = range.split_once('-')?;
+                    let start = start.parse::<usize>().ok().filter(|start| *start > 0)?;
+                    selection.skip = start - 1;
+                    selection.end = match end {
+                        "" => None,
+                        end => Some(end.parse::<usize>().ok().filter(|end| *end >= start)?),
                     };
                 }
             }
-        // handle live stream
-        } else if url.contains("live") {
-            let mut handler = handler_lock.lock().await;
-            let source = match Restartable::ytdl(url.clone(), true).await {
-                Ok(source) => source,
-                Err(why) => {
-                    error!("Err starting source: {:?}", why);
-
-                    msg.channel_id
-                        .send_message(&ctx.http, |m| {
-                            m.embed(|e| {
-                                e.colour(0xf38ba8)
-                                    .title(":warning: Error adding song to playlist.")
-                                    .description("This could mean that the song is unavailable.")
-                                    .timestamp(Timestamp::now())
-                            })
-                        })
-                        .await?;
-                    return Ok(());
-                }
-            };
+        }
+        Some(selection)
+    }
 
-            let song = handler.enqueue_source(source.into());
-            let metadata = song.metadata();
+    // The range is picked first, then put in order and cut to the limit
+    fn apply(&self, tracks: Vec<TrackDescriptor>) -> Vec<TrackDescriptor> {
+        let end = self.end.unwrap_or(tracks.len());
+        let mut tracks = tracks
+            .into_iter()
+            .take(end)
+            .skip(self.skip)
+            .collect::<Vec<_>>();
+
+        if self.reverse {
+            tracks.reverse();
+        }
+        if self.shuffle {
+            tracks.shuffle(&mut rand::thread_rng());
+        }
+        if let Some(limit) = self.limit {
+            tracks.truncate(limit);
+        }
+        tracks
+    }
+}
 
-            msg.channel_id
-                .send_message(&ctx.http, |m| {
-                    m.embed(|e| {
-                        e.colour(0xffffff)
-                            .title(":notes: Added to playlist!")
-                            .thumbnail(metadata.thumbnail.clone().unwrap_or_else(|| String::from("https://images.unsplash.com/photo-1611162616475-46b635cb6868?ixlib=rb-4.0.3")))
-                            .description(format!(
-                                "{} - {}",
-                                metadata.title.clone().unwrap(),
-                                metadata.artist.clone().unwrap()
-                            ))
-                            .fields(vec![
-                                ("Songs queued", format!("{}", handler.queue().len()), true),
-                                ("Total playtime", "infinite".to_string(), true)
-                            ])
-                            .timestamp(Timestamp::now())
-                    })
-                })
-                .await?;
-            return Ok(());
-        // handle direct link to a video
-        } else {
-            let source = match Restartable::ytdl(url.clone(), true).await {
-                Ok(source) => source,
-                Err(why) => {
-                    error!("Err starting source: {:?}", why);
-
-                    msg.channel_id
-                        .send_message(&ctx.http, |m| {
-                            m.embed(|e| {
-                                e.colour(0xf38ba8)
-                                    .title(":warning: Error adding song to playlist.")
-                                    .description("This could mean that the song is unavailable.")
-                                    .timestamp(Timestamp::now())
-                            })
-                        })
-                        .await?;
-                    return Ok(());
-                }
-            };
-            let mut handler = handler_lock.lock().await;
-            let song = handler.enqueue_source(source.into());
-            let mut i = 0;
-            for queued_song in handler.queue().current_queue() {
-                i += queued_song.metadata().duration.unwrap().as_secs();
-            }
-            let playtime = to_time(i);
-            let metadata = song.metadata();
+// Queues the tracks of an M3U, JSON or text playlist file
+async fn play_file(
+    ctx: &Context,
+    msg: &Message,
+    attachment: &Attachment,
+    format: Format,
+) -> CommandResult {
+    let guild = msg.guild(&ctx.cache).unwrap();
+    let settings = guild_settings(&ctx.data, guild.id).await;
+
+    let entries = match attachment.download().await {
+        Ok(raw) => playlist_files::parse(&String::from_utf8_lossy(&raw), format).ok(),
+        Err(why) => {
+            error!("Err downloading '{}': {:?}", attachment.filename, why);
+            None
+        }
+    };
 
+    let entries = match entries {
+        Some(entries) if !entries.is_empty() => entries,
+        _ => {
             msg.channel_id
                 .send_message(&ctx.http, |m| {
                     m.embed(|e| {
-                        e.colour(0xffffff)
-                            .title(":notes: Added to playlist!")
-                            .thumbnail(metadata.thumbnail.clone().unwrap_or_else(|| String::from("https://images.unsplash.com/photo-1611162616475-46b635cb6868?ixlib=rb-4.0.3")))
-                            .description(format!(
-                                "{} - {}",
-                                metadata.title.clone().unwrap(),
-                                metadata.artist.clone().unwrap()
-                            ))
-                            .fields(vec![
-                                ("Songs queued", format!("{}", handler.queue().len()), true),
-                                ("Total playtime", playtime, true)
-                            ])
+                        e.colour(0xf38ba8)
+                            .title(format!(":warning: Couldn't read any songs from {}", attachment.filename))
+                            .description("Attach an M3U, JSON or text file with one link or song name per line.")
                             .timestamp(Timestamp::now())
                     })
                 })
                 .await?;
             return Ok(());
         }
-        let get_raw_list = Command::new("yt-dlp")
-            .args(["-j", "--flat-playlist", &url])
-            .output()
-            .await;
-
-        let raw_list = match get_raw_list {
-            Ok(list) => String::from_utf8(list.stdout).unwrap(),
-            Err(_) => String::from("Error!"),
+    };
+
+    let handler_lock = match join_author(ctx, msg, &guild).await? {
+        Some(handler_lock) => handler_lock,
+        None => return Ok(()),
+    };
+
+    let tracks = entries.into_iter().map(TrackDescriptor::from).collect();
+    queue_tracks(
+        ctx,
+        msg,
+        handler_lock,
+        tracks,
+        &settings,
+        msg.author.id,
+        Placement::Back,
+    )
+    .await
+}
+
+// Audio or video Discord can tell us about, or else one with a known extension
+fn is_media(attachment: &Attachment) -> bool {
+    if let Some(content_type) = &attachment.content_type {
+        if content_type.starts_with("audio/") || content_type.starts_with("video/") {
+            return true;
+        }
+    }
+    has_media_extension(&attachment.filename)
+}
+
+// Streams the attachments from Discord's CDN through ffmpeg, named after the files
+async fn play_attachments(
+    ctx: &Context,
+    msg: &Message,
+    attachments: &[&Attachment],
+) -> CommandResult {
+    let guild = msg.guild(&ctx.cache).unwrap();
+    let settings = guild_settings(&ctx.data, guild.id).await;
+
+    let handler_lock = match join_author(ctx, msg, &guild).await? {
+        Some(handler_lock) => handler_lock,
+        None => return Ok(()),
+    };
+
+    let mut desc = String::new();
+    let mut errors = Vec::new();
+    for attachment in attachments {
+        let mut track = TrackDescriptor::new(Source::Ffmpeg(attachment.url.clone()));
+        track.title = Some(attachment.filename.clone());
+        track.artist = Some(msg.author.name.clone());
+
+        // ffprobe fills in the duration
+        let source = match track.input().await {
+            Ok(source) => source,
+            Err(why) => {
+                error!(
+                    "Err starting source for '{}': {:?}",
+                    attachment.filename, why
+                );
+                errors.push(attachment.filename.clone());
+                continue;
+            }
         };
 
-        let re =
-            Regex::new(r#""url": "(https://www.youtube.com/watch\?v=[A-Za-z0-9]{11})""#).unwrap();
+        let length = match source.input.metadata.duration {
+            Some(duration) => to_time(duration.as_secs()),
+            None => String::from("live"),
+        };
+        desc.push_str(&format!("{} - {}\n", attachment.filename, length));
 
-        let mut urls: Vec<String> = re
-            .captures_iter(&raw_list)
-            .map(|cap| cap[1].to_string())
-            .collect();
+        let mut handler = handler_lock.lock().await;
+        enqueue(&mut handler, source, &settings, msg.author.id).await;
+    }
 
-        let mut msg = msg
-            .channel_id
+    if !desc.is_empty() {
+        let queued = handler_lock.lock().await.queue().len();
+        msg.channel_id
             .send_message(&ctx.http, |m| {
                 m.embed(|e| {
                     e.colour(0xffffff)
-                        .title(":notes: Queuing tracks...")
+                        .title(":notes: Added to playlist!")
+                        .description(desc)
+                        .fields(vec![("Songs queued", queued.to_string(), true)])
+                        .footer(|f| {
+                            f.text("Attachments aren't kept after a restart or in saved playlists")
+                        })
                         .timestamp(Timestamp::now())
                 })
             })
             .await?;
+    }
 
-        info!("Gonna remove tracks from 0..{tracks_to_remove}");
-        urls.drain(0..tracks_to_remove);
-
-        let (tx, _rx): (
-            mpsc::Sender<Option<(usize, TrackHandle)>>,
-            mpsc::Receiver<Option<(usize, TrackHandle)>>,
-        ) = mpsc::channel(urls.len());
-
-        let mut tasks = HashMap::new();
-
-        for (index, url) in urls.iter().enumerate() {
-            let _handler_lock = handler_lock.clone();
-            let _tx = tx.clone();
-            let url = url.clone();
+    if !errors.is_empty() {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: Error adding files to playlist.")
+                        .description(format!(
+                            "{}\nThis could mean that ffmpeg can't read them.",
+                            errors.join("\n")
+                        ))
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+    }
+    Ok(())
+}
 
-            // Spawn a task for each url
-            let task = tokio::spawn(async move {
-                match Restartable::ytdl(url.clone(), true).await {
-                    Ok(source) => (index, Some(source)),
-                    Err(why) => {
-                        error!("Error starting source for URL '{}': {:?}", url, why);
-                        (index, None)
-                    }
-                }
-            });
+// Fetches a single track and queues it
+pub async fn queue_track(
+    ctx: &Context,
+    msg: &Message,
+    handler_lock: Arc<Mutex<Call>>,
+    track: TrackDescriptor,
+    settings: &GuildSettings,
+    requester: UserId,
+    placement: Placement,
+) -> CommandResult {
+    let source = match track.input_retrying().await {
+        Ok(source) => source,
+        Err(why) => {
+            error!("Err starting source: {:?}", why);
 
-            tasks.insert(index, task);
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(":warning: Error adding song to playlist.")
+                            .description(why.to_string())
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
         }
+    };
 
-        // Collect results in the original order
-        let mut results = Vec::new();
-        for index in 0..urls.len() {
-            let res = tasks.remove(&index).unwrap().await.unwrap();
-            results.push(res);
-        }
+    let dj = skips_any(ctx, msg, requester, placement).await;
+    let mut handler = handler_lock.lock().await;
+    let song = enqueue(&mut handler, source, settings, requester).await;
+    let placed = place(&handler, &song, placement, None, requester, dj).await;
+    let metadata = song.metadata();
 
-        // Enqueue tracks
-        let mut errors = 0;
-        for (_index, source) in results {
-            if let Some(source) = source {
-                let mut handler = handler_lock.lock().await;
-                handler.enqueue_source(source.into());
-            } else {
-                errors += 1;
-            }
+    // Links with a timestamp start playing from there
+    if let Some(start) = track.start {
+        if let Err(why) = song.seek_time(start) {
+            error!("Err seeking to {:?}: {:?}", start, why);
         }
+    }
 
-        let mut i = 0;
-        for queued_song in handler_lock.lock().await.queue().current_queue() {
-            i += queued_song.metadata().duration.unwrap().as_secs();
-        }
-        let playtime: String;
-        playtime = to_time(i);
+    // Live streams never end, so neither does the queue
+    let playtime = match metadata.duration {
+        Some(_) => to_time(total_time(&handler.queue().current_queue())),
+        None => String::from("infinite"),
+    };
 
-        let handler = handler_lock.lock().await;
-        msg.edit(&ctx.http, |m| {
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
             m.embed(|e| {
                 e.colour(0xffffff)
-                    .title(":notes: Queued playlist!")
+                    .title(match placed {
+                        Placement::Back => ":notes: Song added to the queue!",
+                        Placement::Next => ":notes: Playing next!",
+                        Placement::Now => ":notes: Playing now!",
+                    })
+                    .thumbnail(metadata.thumbnail.clone().unwrap_or_else(|| String::from("https://images.unsplash.com/photo-1611162616475-46b635cb6868?ixlib=rb-4.0.3")))
+                    .description(format!(
+                        "{} - {}",
+                        metadata.title.clone().unwrap_or_else(|| String::from("Unknown")),
+                        metadata.artist.clone().unwrap_or_else(|| String::from("Unknown"))
+                    ))
                     .fields(vec![
                         ("Songs queued", format!("{}", handler.queue().len()), true),
-                        ("Total playtime", playtime, true),
-                    ])
+                        ("Total playtime", playtime, true)
+                    ]);
+                if placed != placement {
+                    e.footer(|f| f.text(KEPT_CURRENT));
+                }
+                e.timestamp(Timestamp::now())
+            })
+        })
+        .await?;
+
+    Ok(())
+}
+
+// How many tracks of a playlist get fetched at the same time
+fn resolve_concurrency() -> usize {
+    env::var("RESOLVE_CONCURRENCY")
+        .ok()
+        .and_then(|limit| limit.parse().ok())
+        .filter(|limit| *limit > 0)
+        .unwrap_or(4)
+}
+
+// Shows how far along queuing a playlist is
+async fn show_progress(
+    ctx: &Context,
+    msg: &mut Message,
+    resolved: usize,
+    failed: usize,
+    remaining: usize,
+) -> serenity::Result<()> {
+    msg.edit(&ctx.http, |m| {
+        m.embed(|e| {
+            e.colour(0xffffff)
+                .title(":notes: Queuing tracks...")
+                .fields(vec![
+                    ("Queued", resolved.to_string(), true),
+                    ("Failed", failed.to_string(), true),
+                    ("Remaining", remaining.to_string(), true),
+                ])
+                .timestamp(Timestamp::now())
+        })
+    })
+    .await
+}
+
+// Fetches a list of tracks a few at a time and queues each one as soon as the ones
+// before it are in, playlists from anywhere go through here
+pub async fn queue_tracks(
+    ctx: &Context,
+    msg: &Message,
+    handler_lock: Arc<Mutex<Call>>,
+    tracks: Vec<TrackDescriptor>,
+    settings: &GuildSettings,
+    requester: UserId,
+    placement: Placement,
+) -> CommandResult {
+    let total = tracks.len();
+    let mut resolved = 0;
+    let mut failures = Vec::new();
+    // Tracks going to the front stay in order behind the first of them
+    let mut last_placed: Option<TrackHandle> = None;
+    let dj = skips_any(ctx, msg, requester, placement).await;
+    // Set when the current track turned out not to be the requester's to skip
+    let mut kept_current = false;
+
+    let mut msg = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(0xffffff)
+                    .title(":notes: Queuing tracks...")
                     .timestamp(Timestamp::now())
             })
         })
         .await?;
 
-        if errors >= 1 {
-            let er = match errors {
-                1 => format!(":warning: Error adding {errors} song to playlist"),
-                _ => format!(":warning: Error adding {errors} songs to playlist"),
-            };
+    // Every track gets a task, but only a few of them run yt-dlp at once
+    let permits = Arc::new(Semaphore::new(resolve_concurrency()));
+    let labels = tracks
+        .iter()
+        .map(TrackDescriptor::label)
+        .collect::<Vec<_>>();
+    let tasks = tracks
+        .into_iter()
+        .map(|track| {
+            let permits = permits.clone();
+            tokio::spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                track.input_retrying().await
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut progress = interval(PROGRESS_INTERVAL);
+    progress.tick().await;
+
+    // Enqueue tracks in order, updating the progress while waiting on slow ones
+    for (mut task, label) in tasks.into_iter().zip(labels) {
+        let source = loop {
+            tokio::select! {
+                source = &mut task => break source,
+                _ = progress.tick() => {
+                    let failed = failures.len();
+                    let remaining = total - resolved - failed;
+                    if let Err(why) = show_progress(ctx, &mut msg, resolved, failed, remaining).await {
+                        error!("Err updating progress: {:?}", why);
+                    }
+                }
+            }
+        };
+
+        match source {
+            Ok(Ok(source)) => {
+                let mut handler = handler_lock.lock().await;
+                let song = enqueue(&mut handler, source, settings, requester).await;
+                let placed = place(
+                    &handler,
+                    &song,
+                    placement,
+                    last_placed.as_ref(),
+                    requester,
+                    dj,
+                )
+                .await;
+                kept_current |= placed != placement;
+                last_placed = Some(song);
+                resolved += 1;
+            }
+            Ok(Err(why)) => {
+                error!("Error starting source for '{}': {:?}", label, why);
+                failures.push((label, why.to_string()));
+            }
+            Err(why) => {
+                error!("Error starting source for '{}': {:?}", label, why);
+                failures.push((label, String::from("Fetching it crashed.")));
+            }
+        }
+    }
+
+    let handler = handler_lock.lock().await;
+    let playtime = to_time(total_time(&handler.queue().current_queue()));
+
+    msg.edit(&ctx.http, |m| {
+        m.embed(|e| {
+            e.colour(0xffffff)
+                .title(":notes: Queued playlist!")
+                .fields(vec![
+                    ("Songs queued", format!("{}", handler.queue().len()), true),
+                    ("Total playtime", playtime, true),
+                ]);
+            if kept_current {
+                e.footer(|f| f.text(KEPT_CURRENT));
+            }
+            e.timestamp(Timestamp::now())
+        })
+    })
+    .await?;
 
-            msg.channel_id
+    drop(handler);
+
+    if !failures.is_empty() {
+        report_failures(ctx, msg.channel_id, &failures).await?;
+    }
+    Ok(())
+}
+
+// Lists which tracks couldn't be queued and why, as a file when there are too
+// many of them for an embed
+async fn report_failures(
+    ctx: &Context,
+    channel_id: ChannelId,
+    failures: &[(String, String)],
+) -> CommandResult {
+    let title = match failures.len() {
+        1 => String::from(":warning: Error adding 1 song to playlist"),
+        count => format!(":warning: Error adding {count} songs to playlist"),
+    };
+    let lines = failures
+        .iter()
+        .map(|(label, reason)| format!("{label} - {reason}"))
+        .collect::<Vec<_>>();
+
+    if lines.len() <= REPORT_LINES {
+        channel_id
             .send_message(&ctx.http, |m| {
                 m.embed(|e| {
                     e.colour(0xf38ba8)
-                        .title(er)
-                        .description("This could mean that one of the songs in the playlist is unavailable.")
+                        .title(title)
+                        .description(lines.join("\n"))
                         .timestamp(Timestamp::now())
                 })
             })
             .await?;
-        }
+        return Ok(());
     }
 
+    channel_id
+        .send_message(&ctx.http, |m| {
+            m.add_file(AttachmentType::Bytes {
+                data: Cow::from(lines.join("\n").into_bytes()),
+                filename: String::from("failed-songs.txt"),
+            })
+            .embed(|e| {
+                e.colour(0xf38ba8)
+                    .title(title)
+                    .description("The attached file lists every song that failed and why.")
+                    .timestamp(Timestamp::now())
+            })
+        })
+        .await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Ten tracks titled 1 to 10
+    fn playlist() -> Vec<TrackDescriptor> {
+        (1..=10)
+            .map(|n| {
+                let mut track = TrackDescriptor::new(Source::Search(n.to_string()));
+                track.title = Some(n.to_string());
+                track
+            })
+            .collect()
+    }
+
+    fn picked(options: &[&str]) -> Vec<usize> {
+        Selection::parse(options)
+            .unwrap()
+            .apply(playlist())
+            .into_iter()
+            .map(|track| track.title.unwrap().parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(picked(&[]), (1..=10).collect::<Vec<_>>());
+        assert_eq!(picked(&["3-5"]), vec![3, 4, 5]);
+        assert_eq!(picked(&["5-"]), vec![5, 6, 7, 8, 9, 10]);
+        assert_eq!(picked(&["9-20"]), vec![9, 10]);
+        assert_eq!(picked(&["11-"]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn rejects_bad_options() {
+        assert!(Selection::parse(&["0-3"]).is_none());
+        assert!(Selection::parse(&["7-3"]).is_none());
+        assert!(Selection::parse(&["--limit", "0"]).is_none());
+        assert!(Selection::parse(&["--limit"]).is_none());
+        assert!(Selection::parse(&["--limit", "ten"]).is_none());
+        assert!(Selection::parse(&["lofi"]).is_none());
+    }
+
+    #[test]
+    fn reverses_then_limits() {
+        assert_eq!(picked(&["--reverse", "--limit", "3"]), vec![10, 9, 8]);
+        assert_eq!(picked(&["-l", "3", "-r"]), vec![10, 9, 8]);
+        assert_eq!(picked(&["2-6", "--reverse", "--limit", "2"]), vec![6, 5]);
+    }
+
+    #[test]
+    fn shuffles_the_range_before_limiting() {
+        for _ in 0..20 {
+            let tracks = picked(&["4-8", "--shuffle", "--limit", "3"]);
+            assert_eq!(tracks.len(), 3);
+            assert!(tracks.iter().all(|n| (4..=8).contains(n)));
+        }
+
+        let mut tracks = picked(&["--shuffle"]);
+        tracks.sort();
+        assert_eq!(tracks, (1..=10).collect::<Vec<_>>());
+    }
+}
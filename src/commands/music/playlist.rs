@@ -0,0 +1,387 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use std::time::Duration;
+use tracing::error;
+
+use crate::commands::music::play::{queue_tracks, Placement};
+use crate::commands::utils::to_time;
+use crate::player::join_author;
+use crate::player::permissions::{can_queue, is_dj};
+use crate::playlists::{find_playlist, playlists, Playlist, PlaylistTrack, Scope};
+use crate::settings::guild_settings;
+use crate::sources::TrackDescriptor;
+
+#[command]
+#[aliases("pl")]
+#[only_in(guilds)]
+// Saved playlists, personal ones by default or the guild's with --guild
+async fn playlist(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let option = args
+        .single::<String>()
+        .map(|option| option.to_lowercase())
+        .unwrap_or_default();
+
+    // --guild can go anywhere after the option
+    let mut guild_scope = false;
+    let mut words = Vec::new();
+    for word in args.rest().split_whitespace() {
+        match word {
+            "--guild" | "-g" => guild_scope = true,
+            word => words.push(word),
+        }
+    }
+    let name = words.join(" ");
+
+    match (option.as_str(), name.is_empty()) {
+        ("save", false) => save(ctx, msg, &name, guild_scope).await,
+        ("load", false) => load(ctx, msg, &name).await,
+        ("show", false) => show(ctx, msg, &name).await,
+        ("delete", false) => delete(ctx, msg, &name, guild_scope).await,
+        ("list", _) => list(ctx, msg).await,
+        _ => {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(":warning: Use the command like this: playlist <save | load | show | delete> <name> or playlist list")
+                            .description("Add --guild to save or delete a playlist for the whole server.")
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            Ok(())
+        }
+    }
+}
+
+// Snapshots the current queue under the name
+async fn save(ctx: &Context, msg: &Message, name: &str, guild_scope: bool) -> CommandResult {
+    let guild = msg.guild(&ctx.cache).unwrap();
+    let guild_id = guild.id;
+
+    let manager = songbird::get(ctx)
+        .await
+        .expect("Songbird Voice client placed in at initialisation.")
+        .clone();
+
+    let queue = match manager.get(guild_id) {
+        Some(handler_lock) => handler_lock.lock().await.queue().current_queue(),
+        None => Vec::new(),
+    };
+
+    let tracks = queue
+        .iter()
+        .filter_map(PlaylistTrack::from_track)
+        .collect::<Vec<_>>();
+
+    if tracks.is_empty() {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: The queue is empty, there's nothing to save.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let scope = match guild_scope {
+        true => Scope::Guild(guild_id),
+        false => Scope::User(msg.author.id),
+    };
+
+    let playlists = playlists(&ctx.data).await;
+    let mut playlists = playlists.write().await;
+
+    // Overwriting somebody else's guild playlist takes a DJ
+    if let Some(existing) = playlists.get(scope, name) {
+        if existing.owner != msg.author.id && !is_dj(ctx, &guild, msg.author.id).await {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(format!(
+                                ":warning: {} belongs to someone else, pick another name.",
+                                existing.name
+                            ))
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+    }
+
+    let count = tracks.len();
+    let playlist = Playlist {
+        name: name.to_string(),
+        owner: msg.author.id,
+        tracks,
+    };
+
+    if let Err(why) = playlists.insert(scope, playlist).await {
+        error!("Failed to save playlists: {:?}", why);
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title("Failed to save the playlist.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let description = match scope {
+        Scope::Guild(_) => format!("{} songs, everyone on the server can load it.", count),
+        Scope::User(_) => format!("{} songs, only you can load it.", count),
+    };
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(0xffffff)
+                    .title(format!(":floppy_disk: Saved playlist {}!", name))
+                    .description(description)
+                    .timestamp(Timestamp::now())
+            })
+        })
+        .await?;
+    Ok(())
+}
+
+// Queues every song of the playlist the same way play queues a YouTube playlist
+async fn load(ctx: &Context, msg: &Message, name: &str) -> CommandResult {
+    let guild = msg.guild(&ctx.cache).unwrap();
+    let guild_id = guild.id;
+
+    if !can_queue(ctx, &guild, msg.author.id).await {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: The queue is locked to DJs right now.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let playlist = match find_playlist(&ctx.data, msg.author.id, guild_id, name).await {
+        Some((_, playlist)) => playlist,
+        None => return not_found(ctx, msg, name).await,
+    };
+
+    let handler_lock = match join_author(ctx, msg, &guild).await? {
+        Some(handler_lock) => handler_lock,
+        None => return Ok(()),
+    };
+
+    let settings = guild_settings(&ctx.data, guild_id).await;
+    // What was saved stands in until the tracks are fetched again
+    let tracks = playlist
+        .tracks
+        .into_iter()
+        .map(|track| {
+            let mut descriptor = TrackDescriptor::from_url(&track.url);
+            descriptor.title = track.title;
+            descriptor.duration = track.duration.map(Duration::from_secs);
+            descriptor
+        })
+        .collect();
+
+    queue_tracks(
+        ctx,
+        msg,
+        handler_lock,
+        tracks,
+        &settings,
+        msg.author.id,
+        Placement::Back,
+    )
+    .await
+}
+
+async fn list(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let (own, shared) = {
+        let playlists = playlists(&ctx.data).await;
+        let playlists = playlists.read().await;
+        (
+            playlists.list(Scope::User(msg.author.id)),
+            playlists.list(Scope::Guild(guild_id)),
+        )
+    };
+
+    if own.is_empty() && shared.is_empty() {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(
+                            ":warning: No playlists yet, save the queue with playlist save <name>.",
+                        )
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let describe = |playlists: &[Playlist]| match playlists.is_empty() {
+        true => String::from("None"),
+        false => playlists
+            .iter()
+            .map(|playlist| format!("{} - {} songs", playlist.name, playlist.tracks.len()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(0xffffff)
+                    .title(":scroll: - Playlists - :scroll:")
+                    .fields(vec![
+                        ("Yours", describe(&own), false),
+                        ("This server's", describe(&shared), false),
+                    ])
+                    .timestamp(Timestamp::now())
+            })
+        })
+        .await?;
+    Ok(())
+}
+
+async fn show(ctx: &Context, msg: &Message, name: &str) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+
+    let (scope, playlist) = match find_playlist(&ctx.data, msg.author.id, guild_id, name).await {
+        Some(found) => found,
+        None => return not_found(ctx, msg, name).await,
+    };
+
+    let mut desc = String::from("+ - + - + - + - + - + - + - + - + - +\n");
+    let mut total_time = 0;
+    for (i, track) in playlist.tracks.iter().enumerate() {
+        total_time += track.duration.unwrap_or(0);
+
+        let title = track
+            .title
+            .clone()
+            .unwrap_or_else(|| String::from("Unknown"));
+        let line = format!("{}. [{}]({})\n", i + 1, title, track.url);
+
+        // embed descriptions are capped at 4096 characters
+        if desc.len() + line.len() > 4000 {
+            continue;
+        }
+        desc.push_str(&line);
+    }
+
+    let owner = match scope {
+        Scope::Guild(_) => format!("{} (server)", playlist.owner.mention()),
+        Scope::User(_) => playlist.owner.mention().to_string(),
+    };
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(0xffffff)
+                    .title(format!(":scroll: - {} - :scroll:", playlist.name))
+                    .fields(vec![
+                        ("Songs", playlist.tracks.len().to_string(), true),
+                        ("Total time", to_time(total_time), true),
+                        ("Saved by", owner, true),
+                    ])
+                    .description(desc)
+                    .timestamp(Timestamp::now())
+            })
+        })
+        .await?;
+    Ok(())
+}
+
+async fn delete(ctx: &Context, msg: &Message, name: &str, guild_scope: bool) -> CommandResult {
+    let guild = msg.guild(&ctx.cache).unwrap();
+    let guild_id = guild.id;
+
+    let found = match guild_scope {
+        true => {
+            let playlists = playlists(&ctx.data).await;
+            let playlist = playlists.read().await.get(Scope::Guild(guild_id), name);
+            playlist.map(|playlist| (Scope::Guild(guild_id), playlist))
+        }
+        false => find_playlist(&ctx.data, msg.author.id, guild_id, name).await,
+    };
+
+    let (scope, playlist) = match found {
+        Some(found) => found,
+        None => return not_found(ctx, msg, name).await,
+    };
+
+    // Server playlists can be deleted by whoever saved them and by DJs
+    if let Scope::Guild(_) = scope {
+        if playlist.owner != msg.author.id && !is_dj(ctx, &guild, msg.author.id).await {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(
+                                ":warning: Only DJs or whoever saved the playlist can delete it.",
+                            )
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+    }
+
+    let playlists = playlists(&ctx.data).await;
+    let removed = playlists.write().await.remove(scope, name).await;
+    if let Err(why) = removed {
+        error!("Failed to save playlists: {:?}", why);
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title("Failed to delete the playlist.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(0xffffff)
+                    .title(format!(":wastebasket: Deleted playlist {}!", playlist.name))
+                    .timestamp(Timestamp::now())
+            })
+        })
+        .await?;
+    Ok(())
+}
+
+async fn not_found(ctx: &Context, msg: &Message, name: &str) -> CommandResult {
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(0xf38ba8)
+                    .title(format!(":warning: There's no playlist called {}.", name))
+                    .timestamp(Timestamp::now())
+            })
+        })
+        .await?;
+    Ok(())
+}
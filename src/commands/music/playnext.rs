@@ -0,0 +1,15 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::commands::music::play::{play_query, Placement};
+use crate::player::permissions::DJONLY_CHECK;
+
+#[command]
+#[aliases(pn)]
+#[only_in(guilds)]
+#[checks(DjOnly)]
+async fn playnext(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    play_query(ctx, msg, args.rest(), Placement::Next).await
+}
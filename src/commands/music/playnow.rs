@@ -0,0 +1,47 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::commands::music::play::{play_query, Placement};
+use crate::player::permissions::{is_dj, DJONLY_CHECK};
+use crate::player::RequestedBy;
+
+#[command]
+#[only_in(guilds)]
+#[checks(DjOnly)]
+async fn playnow(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let guild = msg.guild(&ctx.cache).unwrap();
+
+    let manager = songbird::get(ctx)
+        .await
+        .expect("Songbird Voice client placed in at initialisation.")
+        .clone();
+
+    // Skipping the current song takes the same rights as skip without a vote
+    let current = match manager.get(guild.id) {
+        Some(handler_lock) => {
+            let handler = handler_lock.lock().await;
+            handler.queue().current()
+        }
+        None => None,
+    };
+    if let Some(current) = current {
+        let requester = current.typemap().read().await.get::<RequestedBy>().copied();
+        if requester != Some(msg.author.id) && !is_dj(ctx, &guild, msg.author.id).await {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(":warning: Only the requester of the current song or a DJ can use playnow.")
+                            .description("Use playnext to queue it right after the current song instead.")
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+    }
+
+    play_query(ctx, msg, args.rest(), Placement::Now).await
+}
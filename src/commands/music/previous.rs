@@ -0,0 +1,108 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::CommandResult;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use tracing::error;
+
+use crate::player::permissions::DJONLY_CHECK;
+use crate::player::state::with_guild_state;
+use crate::player::{enqueue, play_first, source_from_url};
+use crate::settings::guild_settings;
+
+#[command]
+#[aliases("prev", "back")]
+#[only_in(guilds)]
+#[checks(DjOnly)]
+async fn previous(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild = msg.guild(&ctx.cache).unwrap();
+    let guild_id = guild.id;
+
+    let manager = songbird::get(ctx)
+        .await
+        .expect("Songbird Voice client placed in at initialisation.")
+        .clone();
+
+    if let Some(handler_lock) = manager.get(guild_id) {
+        // Taking the entry out means running previous again keeps going further back
+        let entry = with_guild_state(&ctx.data, guild_id, |state| state.history.pop_front()).await;
+
+        let (entry, url) = match entry {
+            Some(entry) => match entry.source_url.clone() {
+                Some(url) => (entry, url),
+                None => {
+                    msg.channel_id
+                        .send_message(&ctx.http, |m| {
+                            m.embed(|e| {
+                                e.colour(0xf38ba8)
+                                    .title(":warning: The previous song can't be played again.")
+                                    .timestamp(Timestamp::now())
+                            })
+                        })
+                        .await?;
+                    return Ok(());
+                }
+            },
+            None => {
+                msg.channel_id
+                    .send_message(&ctx.http, |m| {
+                        m.embed(|e| {
+                            e.colour(0xf38ba8)
+                                .title(":warning: Nothing has been played yet.")
+                                .timestamp(Timestamp::now())
+                        })
+                    })
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let source = match source_from_url(&url).await {
+            Ok(source) => source,
+            Err(why) => {
+                error!("Err starting source: {:?}", why);
+                with_guild_state(&ctx.data, guild_id, |state| state.history.push_front(entry))
+                    .await;
+
+                msg.channel_id
+                    .send_message(&ctx.http, |m| {
+                        m.embed(|e| {
+                            e.colour(0xf38ba8)
+                                .title(":warning: Error adding song to playlist.")
+                                .description("This could mean that the song is unavailable.")
+                                .timestamp(Timestamp::now())
+                        })
+                    })
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let settings = guild_settings(&ctx.data, guild_id).await;
+        let mut handler = handler_lock.lock().await;
+        let song = enqueue(&mut handler, source, &settings, msg.author.id).await;
+        play_first(&handler, &song);
+
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xffffff)
+                        .title(":track_previous: Playing the previous song!")
+                        .description(entry.title)
+                        .url(url)
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+    } else {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: Not in a voice channel.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+    }
+    Ok(())
+}
@@ -1,9 +1,14 @@
 use crate::commands::utils::to_time;
+use crate::player::total_time;
+use crate::settings::guild_settings;
 use serenity::framework::standard::macros::command;
 use serenity::framework::standard::CommandResult;
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 
+// How many tracks the queue lists
+const QUEUE_PAGE: usize = 20;
+
 #[command]
 #[only_in(guilds)]
 async fn queue(ctx: &Context, msg: &Message) -> CommandResult {
@@ -36,20 +41,28 @@ async fn queue(ctx: &Context, msg: &Message) -> CommandResult {
             }
         };
 
+        let tracks = queue.current_queue();
         let mut desc = String::from("+ - + - + - + - + - + - + - + - + - +\n");
-        let mut total_time = 0;
-        for (i, song) in queue.current_queue().iter().enumerate() {
+        for (i, song) in tracks.iter().take(QUEUE_PAGE).enumerate() {
             desc.push_str(&format!(
                 "{}. {} - {}\n",
                 i + 1,
-                song.metadata().title.clone().unwrap(),
+                song.metadata()
+                    .title
+                    .clone()
+                    .unwrap_or_else(|| String::from("Unknown")),
                 song.metadata()
                     .artist
                     .clone()
                     .unwrap_or_else(|| String::from("Unknown"))
             ));
-            total_time += song.metadata().duration.unwrap().as_secs()
         }
+        // Embeds only fit so much, long playlists get cut off
+        if tracks.len() > QUEUE_PAGE {
+            desc.push_str(&format!("... and {} more\n", tracks.len() - QUEUE_PAGE));
+        }
+
+        let settings = guild_settings(&ctx.data, guild_id).await;
 
         msg.channel_id
             .send_message(&ctx.http, |m| {
@@ -58,7 +71,8 @@ async fn queue(ctx: &Context, msg: &Message) -> CommandResult {
                         .title(":notes: - Queue - :notes:")
                         .fields(vec![
                             ("Queue length", format!("{}", queue.len()), true),
-                            ("Total time", to_time(total_time), true),
+                            ("Total time", to_time(total_time(&tracks)), true),
+                            ("Loop", settings.loop_mode.to_string(), true),
                         ])
                         .description(desc)
                         .timestamp(Timestamp::now())
@@ -0,0 +1,138 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::player::discard;
+use crate::player::permissions::{is_dj, requested_all, DJONLY_CHECK};
+
+#[command]
+#[aliases("rm")]
+#[only_in(guilds)]
+#[checks(DjOnly)]
+async fn remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let (from, to) = match args.single::<String>().ok().and_then(|r| parse_range(&r)) {
+        Some(range) => range,
+        None => {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(":warning: Use the command like this: remove <n> or remove <from>-<to>")
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let guild = msg.guild(&ctx.cache).unwrap();
+    let guild_id = guild.id;
+
+    let manager = songbird::get(ctx)
+        .await
+        .expect("Songbird Voice client placed in at initialisation.")
+        .clone();
+
+    if let Some(handler_lock) = manager.get(guild_id) {
+        let handler = handler_lock.lock().await;
+        let queue = handler.queue();
+
+        // 1 is the track that is playing right now, skip is the way to get rid of it
+        if from < 2 || to > queue.len() {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(":warning: Invalid position.")
+                            .description(format!(
+                                "Pick a position between 2 and {}, use skip for the current song.",
+                                queue.len()
+                            ))
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+
+        // Besides DJs, people can only remove songs they queued themselves
+        let tracks = queue.current_queue();
+        if !requested_all(&tracks[from - 1..to], msg.author.id).await
+            && !is_dj(ctx, &guild, msg.author.id).await
+        {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(":warning: You can only remove songs you queued yourself.")
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+
+        let removed = queue.modify_queue(|queue| queue.drain(from - 1..to).collect::<Vec<_>>());
+
+        let mut desc = String::new();
+        for (i, track) in removed.iter().enumerate() {
+            desc.push_str(&format!(
+                "{}. {}\n",
+                from + i,
+                track
+                    .metadata()
+                    .title
+                    .clone()
+                    .unwrap_or_else(|| String::from("Unknown"))
+            ));
+            discard(track).await;
+        }
+
+        let title = match removed.len() {
+            1 => String::from(":wastebasket: Removed 1 song from the queue"),
+            n => format!(":wastebasket: Removed {n} songs from the queue"),
+        };
+
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xffffff)
+                        .title(title)
+                        .description(desc)
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+    } else {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: Not in a voice channel.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+    }
+    Ok(())
+}
+
+// Parses "3" or "3-5" into an inclusive range of queue positions
+fn parse_range(input: &str) -> Option<(usize, usize)> {
+    match input.split_once('-') {
+        Some((from, to)) => {
+            let from = from.trim().parse().ok()?;
+            let to = to.trim().parse().ok()?;
+            if from > to {
+                return None;
+            }
+            Some((from, to))
+        }
+        None => {
+            let n = input.trim().parse().ok()?;
+            Some((n, n))
+        }
+    }
+}
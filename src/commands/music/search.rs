@@ -0,0 +1,142 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use std::time::Duration;
+use tracing::error;
+
+use crate::commands::music::play::{queue_track, Placement};
+use crate::commands::utils::to_time;
+use crate::player::join_author;
+use crate::player::permissions::DJONLY_CHECK;
+use crate::settings::guild_settings;
+use crate::sources::search::search as search_youtube;
+
+// How many results to pick from and how long to wait for the pick
+const SEARCH_RESULTS: usize = 5;
+const PICK_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[command]
+#[aliases(find)]
+#[only_in(guilds)]
+#[checks(DjOnly)]
+async fn search(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let query = args.rest().trim();
+    if query.is_empty() {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: Use the command like this: search <song name>")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let results = match search_youtube(query, SEARCH_RESULTS).await {
+        Ok(results) => results,
+        Err(why) => {
+            error!("Err searching for '{}': {:?}", query, why);
+
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(format!(":warning: Couldn't search for {query}"))
+                            .description(why.to_string())
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let mut desc = String::new();
+    for (index, track) in results.iter().enumerate() {
+        let length = match track.duration {
+            Some(duration) => to_time(duration.as_secs()),
+            None => String::from("live"),
+        };
+        desc.push_str(&format!(
+            "**{}.** {} - {} ({})\n",
+            index + 1,
+            track.title.as_deref().unwrap_or("Unknown"),
+            track.artist.as_deref().unwrap_or("Unknown"),
+            length
+        ));
+    }
+
+    let mut list = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(0xffffff)
+                    .title(format!(":mag: Results for {query}"))
+                    .description(desc)
+                    .footer(|f| {
+                        f.text(format!(
+                            "Reply with a number within {} seconds, or cancel",
+                            PICK_TIMEOUT.as_secs()
+                        ))
+                    })
+                    .timestamp(Timestamp::now())
+            })
+        })
+        .await?;
+
+    // Only replies from whoever searched that are a number on the list or cancel
+    let count = results.len();
+    let reply = msg
+        .author
+        .await_reply(ctx)
+        .channel_id(msg.channel_id)
+        .timeout(PICK_TIMEOUT)
+        .filter(move |reply| {
+            let content = reply.content.trim();
+            content.eq_ignore_ascii_case("cancel")
+                || content
+                    .parse::<usize>()
+                    .map(|pick| (1..=count).contains(&pick))
+                    .unwrap_or(false)
+        })
+        .await;
+
+    let pick = match reply.and_then(|reply| reply.content.trim().parse::<usize>().ok()) {
+        Some(pick) => pick,
+        None => {
+            list.edit(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":x: Nothing was picked.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+            return Ok(());
+        }
+    };
+
+    let track = results.into_iter().nth(pick - 1).unwrap();
+
+    let guild = msg.guild(&ctx.cache).unwrap();
+    let settings = guild_settings(&ctx.data, guild.id).await;
+
+    let handler_lock = match join_author(ctx, msg, &guild).await? {
+        Some(handler_lock) => handler_lock,
+        None => return Ok(()),
+    };
+
+    queue_track(
+        ctx,
+        msg,
+        handler_lock,
+        track,
+        &settings,
+        msg.author.id,
+        Placement::Back,
+    )
+    .await
+}
@@ -0,0 +1,196 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use std::time::Duration;
+use tracing::error;
+
+use crate::commands::utils::{parse_time, to_position, to_time};
+
+#[command]
+#[only_in(guilds)]
+async fn seek(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let position = match args.single::<String>().ok().and_then(|t| parse_time(&t)) {
+        Some(secs) => Duration::from_secs(secs),
+        None => {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(":warning: Use the command like this: seek <1:23 | 1:02:03 | 90 | 1h2m3s>")
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+    };
+
+    seek_current(ctx, msg, String::from(":fast_forward: Seeked!"), |_, _| {
+        position
+    })
+    .await
+}
+
+// Shared by ff and rewind, moves the current track by an offset relative to its position
+pub async fn seek_relative(
+    ctx: &Context,
+    msg: &Message,
+    mut args: Args,
+    forward: bool,
+) -> CommandResult {
+    let offset = match args.single::<String>().ok().and_then(|t| parse_time(&t)) {
+        Some(secs) => Duration::from_secs(secs),
+        None => {
+            let usage = match forward {
+                true => ":warning: Use the command like this: ff <30 | 1:30 | 1m30s>",
+                false => ":warning: Use the command like this: rewind <30 | 1:30 | 1m30s>",
+            };
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| e.colour(0xf38ba8).title(usage).timestamp(Timestamp::now()))
+                })
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let title = match forward {
+        true => format!(":fast_forward: Forwarded {}", to_time(offset.as_secs())),
+        false => format!(":rewind: Rewound {}", to_time(offset.as_secs())),
+    };
+
+    seek_current(ctx, msg, title, |position, duration| match forward {
+        true => position.saturating_add(offset).min(duration),
+        false => position.saturating_sub(offset),
+    })
+    .await
+}
+
+// Moves the current track to the position `target` picks from where it is now
+// and how long it is
+async fn seek_current<F>(ctx: &Context, msg: &Message, title: String, target: F) -> CommandResult
+where
+    F: FnOnce(Duration, Duration) -> Duration,
+{
+    let guild = msg.guild(&ctx.cache).unwrap();
+    let guild_id = guild.id;
+
+    let manager = songbird::get(ctx)
+        .await
+        .expect("Songbird Voice client placed in at initialisation.")
+        .clone();
+
+    if let Some(handler_lock) = manager.get(guild_id) {
+        let handler = handler_lock.lock().await;
+        let queue = handler.queue();
+
+        let current = match queue.current() {
+            Some(current) => current,
+            None => {
+                msg.channel_id
+                    .send_message(&ctx.http, |m| {
+                        m.embed(|e| {
+                            e.colour(0xf38ba8)
+                                .title(":warning: Nothing is playing right now.")
+                                .timestamp(Timestamp::now())
+                        })
+                    })
+                    .await?;
+
+                return Ok(());
+            }
+        };
+
+        // live streams have no duration, so there is nothing to move within
+        let duration = match current.metadata().duration {
+            Some(duration) if current.is_seekable() => duration,
+            _ => {
+                msg.channel_id
+                    .send_message(&ctx.http, |m| {
+                        m.embed(|e| {
+                            e.colour(0xf38ba8)
+                                .title(":warning: This track can't be seeked.")
+                                .timestamp(Timestamp::now())
+                        })
+                    })
+                    .await?;
+
+                return Ok(());
+            }
+        };
+
+        let track_info = match current.get_info().await {
+            Ok(track_info) => track_info,
+            Err(why) => {
+                error!("Failed to get track info: {:?}", why);
+                msg.channel_id
+                    .send_message(&ctx.http, |m| {
+                        m.embed(|e| {
+                            e.colour(0xf38ba8)
+                                .title("Failed to seek track.")
+                                .timestamp(Timestamp::now())
+                        })
+                    })
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let position = target(track_info.position, duration);
+        if position > duration {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(":warning: Position is past the end of the track.")
+                            .description(format!("Track length is {}", to_time(duration.as_secs())))
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+
+            return Ok(());
+        }
+
+        if let Err(why) = current.seek_time(position) {
+            error!("Failed to seek track: {:?}", why);
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title("Failed to seek track.")
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xffffff)
+                        .title(title)
+                        .fields(vec![(
+                            "Position",
+                            to_position(position, Some(duration)),
+                            true,
+                        )])
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+    } else {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: Not in a voice channel.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+    }
+    Ok(())
+}
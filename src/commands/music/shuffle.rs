@@ -5,8 +5,11 @@ use serenity::framework::standard::CommandResult;
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 
+use crate::player::permissions::{DJONLY_CHECK, DJ_CHECK};
+
 #[command]
 #[only_in(guilds)]
+#[checks(DjOnly, DJ)]
 async fn shuffle(ctx: &Context, msg: &Message) -> CommandResult {
     let guild = msg.guild(&ctx.cache).unwrap();
     let guild_id = guild.id;
@@ -1,11 +1,40 @@
 use serenity::framework::standard::macros::command;
-use serenity::framework::standard::CommandResult;
+use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::prelude::*;
 use serenity::prelude::*;
+use std::collections::HashSet;
+
+use crate::player::permissions::is_dj;
+use crate::player::permissions::DJONLY_CHECK;
+use crate::player::{discard, listeners, RequestedBy, SkipVotes};
+use crate::settings::guild_settings;
 
 #[command]
+#[aliases("skipto")]
 #[only_in(guilds)]
-async fn skip(ctx: &Context, msg: &Message) -> CommandResult {
+#[checks(DjOnly)]
+async fn skip(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    // Position in the queue to skip to, plain skip goes to the next song
+    let skip_to = !args.is_empty();
+    let target = match skip_to {
+        false => 2,
+        true => match args.single::<usize>() {
+            Ok(target) => target,
+            Err(_) => {
+                msg.channel_id
+                    .send_message(&ctx.http, |m| {
+                        m.embed(|e| {
+                            e.colour(0xf38ba8)
+                                .title(":warning: Use the command like this: skip or skip <n>")
+                                .timestamp(Timestamp::now())
+                        })
+                    })
+                    .await?;
+                return Ok(());
+            }
+        },
+    };
+
     let guild = msg.guild(&ctx.cache).unwrap();
     let guild_id = guild.id;
 
@@ -17,13 +46,145 @@ async fn skip(ctx: &Context, msg: &Message) -> CommandResult {
     if let Some(handler_lock) = manager.get(guild_id) {
         let handler = handler_lock.lock().await;
         let queue = handler.queue();
+
+        let current = match queue.current() {
+            Some(current) => current,
+            None => {
+                msg.channel_id
+                    .send_message(&ctx.http, |m| {
+                        m.embed(|e| {
+                            e.colour(0xf38ba8)
+                                .title(":warning: Nothing is playing right now.")
+                                .timestamp(Timestamp::now())
+                        })
+                    })
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        // The requester and DJs skip right away, everyone else has to vote
+        let requester = current.typemap().read().await.get::<RequestedBy>().copied();
+        let forced = requester == Some(msg.author.id) || is_dj(ctx, &guild, msg.author.id).await;
+
+        if !forced {
+            if skip_to {
+                msg.channel_id
+                    .send_message(&ctx.http, |m| {
+                        m.embed(|e| {
+                            e.colour(0xf38ba8)
+                                .title(
+                                    ":warning: Only the requester or a DJ can skip to a position.",
+                                )
+                                .timestamp(Timestamp::now())
+                        })
+                    })
+                    .await?;
+                return Ok(());
+            }
+
+            let listeners = listeners(ctx, &guild);
+            if !listeners.contains(&msg.author.id) {
+                msg.channel_id
+                    .send_message(&ctx.http, |m| {
+                        m.embed(|e| {
+                            e.colour(0xf38ba8)
+                                .title(":warning: Join my voice channel to vote.")
+                                .timestamp(Timestamp::now())
+                        })
+                    })
+                    .await?;
+                return Ok(());
+            }
+
+            let settings = guild_settings(&ctx.data, guild_id).await;
+            let needed =
+                (listeners.len() as f32 * settings.skip_ratio as f32 / 100.0).ceil() as usize;
+            let needed = needed.max(1);
+
+            let votes = {
+                let mut typemap = current.typemap().write().await;
+                match typemap.get_mut::<SkipVotes>() {
+                    Some(votes) => {
+                        votes.insert(msg.author.id);
+                    }
+                    None => {
+                        typemap.insert::<SkipVotes>(HashSet::from([msg.author.id]));
+                    }
+                }
+                // people who left the channel since voting don't count anymore
+                typemap
+                    .get::<SkipVotes>()
+                    .map(|votes| votes.iter().filter(|user| listeners.contains(user)).count())
+                    .unwrap_or(0)
+            };
+
+            if votes < needed {
+                msg.channel_id
+                    .send_message(&ctx.http, |m| {
+                        m.embed(|e| {
+                            e.colour(0xffffff)
+                                .title(":ballot_box: Voted to skip!")
+                                .fields(vec![("Votes", format!("{votes}/{needed} votes"), true)])
+                                .timestamp(Timestamp::now())
+                        })
+                    })
+                    .await?;
+                return Ok(());
+            }
+        }
+
+        // Plain skip is allowed on the last song, it just ends the queue
+        if target < 2 || (skip_to && target > queue.len()) {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(":warning: Invalid position.")
+                            .description(format!("Pick a position between 2 and {}.", queue.len()))
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+
+        // Drop everything between the current song and the target
+        let dropped = queue.modify_queue(|queue| match target > 2 {
+            true => queue.drain(1..target - 1).collect::<Vec<_>>(),
+            false => Vec::new(),
+        });
+        for track in &dropped {
+            discard(track).await;
+        }
+
+        let next = queue
+            .current_queue()
+            .get(1)
+            .map(|track| {
+                track
+                    .metadata()
+                    .title
+                    .clone()
+                    .unwrap_or_else(|| String::from("Unknown"))
+            })
+            .unwrap_or_else(|| String::from("Nothing, the queue is empty"));
+
         let _ = queue.skip();
 
+        let title = match dropped.len() {
+            0 if !forced => String::from(":track_next: Vote passed, skipped!"),
+            0 => String::from(":track_next: Skipped!"),
+            1 => String::from(":track_next: Skipped! Dropped 1 song from the queue"),
+            n => format!(":track_next: Skipped! Dropped {n} songs from the queue"),
+        };
+
         msg.channel_id
             .send_message(&ctx.http, |m| {
                 m.embed(|e| {
                     e.colour(0xffffff)
-                        .title(":track_next: Skipped!")
+                        .title(title)
+                        .fields(vec![("Now playing", next, false)])
                         .timestamp(Timestamp::now())
                 })
             })
@@ -0,0 +1,91 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use tracing::error;
+
+use crate::player::permissions::is_dj;
+use crate::settings::{guild_settings, update_guild_settings};
+
+#[command]
+#[aliases("voteskip")]
+#[only_in(guilds)]
+async fn skipratio(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild = msg.guild(&ctx.cache).unwrap();
+    let guild_id = guild.id;
+
+    // Without an argument just show the current ratio
+    if args.is_empty() {
+        let settings = guild_settings(&ctx.data, guild_id).await;
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xffffff)
+                        .title(format!(
+                            ":ballot_box: {}% of listeners have to vote to skip",
+                            settings.skip_ratio
+                        ))
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    if !is_dj(ctx, &guild, msg.author.id).await {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: Only DJs can change the skip ratio.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let ratio = match args.single::<u8>() {
+        Ok(ratio) if (1..=100).contains(&ratio) => ratio,
+        _ => {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(":warning: Use the command like this: skipratio <1-100>")
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if let Err(why) = update_guild_settings(&ctx.data, guild_id, |s| s.skip_ratio = ratio).await {
+        error!("Failed to save settings: {:?}", why);
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title("Failed to save skip ratio.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(0xffffff)
+                    .title(format!(
+                        ":ballot_box: Skips now need {}% of listeners to vote",
+                        ratio
+                    ))
+                    .timestamp(Timestamp::now())
+            })
+        })
+        .await?;
+    Ok(())
+}
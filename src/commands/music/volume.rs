@@ -0,0 +1,85 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use tracing::error;
+
+use crate::settings::{guild_settings, update_guild_settings};
+
+#[command]
+#[aliases("vol")]
+#[only_in(guilds)]
+async fn volume(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild = msg.guild(&ctx.cache).unwrap();
+    let guild_id = guild.id;
+
+    // Without an argument just show the current volume
+    if args.is_empty() {
+        let settings = guild_settings(&ctx.data, guild_id).await;
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xffffff)
+                        .title(format!(":loud_sound: Volume is {}%", settings.volume))
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let volume = match args.single::<u8>() {
+        Ok(volume) if volume <= 200 => volume,
+        _ => {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(":warning: Use the command like this: volume <0-200>")
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if let Err(why) = update_guild_settings(&ctx.data, guild_id, |s| s.volume = volume).await {
+        error!("Failed to save settings: {:?}", why);
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title("Failed to save volume.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let manager = songbird::get(ctx)
+        .await
+        .expect("Songbird Voice client placed in at initialisation.")
+        .clone();
+
+    // Apply to everything already queued, later tracks pick it up in play
+    if let Some(handler_lock) = manager.get(guild_id) {
+        let settings = guild_settings(&ctx.data, guild_id).await;
+        let handler = handler_lock.lock().await;
+        for track in handler.queue().current_queue() {
+            let _ = track.set_volume(settings.volume());
+        }
+    }
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(0xffffff)
+                    .title(format!(":loud_sound: Volume set to {}%", volume))
+                    .timestamp(Timestamp::now())
+            })
+        })
+        .await?;
+    Ok(())
+}
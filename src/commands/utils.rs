@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 pub fn to_time(secs: u64) -> String {
     let sec = (secs % 60) as u8;
     let min = ((secs / 60) % 60) as u8;
@@ -8,3 +10,104 @@ pub fn to_time(secs: u64) -> String {
     }
     format!("{}:{:0>2}:{:0>2}", hrs, min, sec)
 }
+
+// Inverse of to_time, also accepts plain seconds ("90") and unit form ("1h2m3s")
+pub fn parse_time(input: &str) -> Option<u64> {
+    let input = input.trim().to_lowercase();
+    if input.is_empty() {
+        return None;
+    }
+
+    if input.contains(':') {
+        let parts: Vec<&str> = input.split(':').collect();
+        if parts.len() > 3 {
+            return None;
+        }
+
+        let mut secs: u64 = 0;
+        for (i, part) in parts.iter().enumerate() {
+            let value: u64 = part.parse().ok()?;
+            // everything but the leading field is limited to 0..60
+            if i > 0 && value >= 60 {
+                return None;
+            }
+            secs = secs.checked_mul(60)?.checked_add(value)?;
+        }
+        return Some(secs);
+    }
+
+    if let Ok(secs) = input.parse::<u64>() {
+        return Some(secs);
+    }
+
+    let mut secs = 0;
+    let mut number = String::new();
+    let mut last_unit = u64::MAX;
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        // units have to be given once each, from largest to smallest
+        if number.is_empty() || unit >= last_unit {
+            return None;
+        }
+        secs = number
+            .parse::<u64>()
+            .ok()?
+            .checked_mul(unit)
+            .and_then(|value| value.checked_add(secs))?;
+        number.clear();
+        last_unit = unit;
+    }
+
+    if !number.is_empty() {
+        return None;
+    }
+    Some(secs)
+}
+
+// Whether a track's source is something Discord can link to, local tracks only
+// have a path
+pub fn is_link(source: &str) -> bool {
+    source.starts_with("https://") || source.starts_with("http://")
+}
+
+// Formats a track position the way nowplaying shows it
+pub fn to_position(position: Duration, duration: Option<Duration>) -> String {
+    match duration {
+        Some(duration) => format!(
+            "{} - {}",
+            to_time(position.as_secs()),
+            to_time(duration.as_secs())
+        ),
+        None => format!("{} - live", to_time(position.as_secs())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_time_reads_back_to_time() {
+        for secs in [0, 59, 60, 61, 3599, 3600, 3661, 86_400, 1_000_000] {
+            assert_eq!(parse_time(&to_time(secs)), Some(secs));
+        }
+    }
+
+    #[test]
+    fn parse_time_rejects_overflow() {
+        assert_eq!(parse_time("999999999999999999999"), None);
+        assert_eq!(parse_time("999999999999999999:00:00"), None);
+        assert_eq!(parse_time("9999999999999999h"), None);
+        assert_eq!(parse_time("5124095576030431h59m59s"), None);
+    }
+}
@@ -0,0 +1,270 @@
+use lofty::{Accessor, AudioFile, TaggedFileExt};
+use serenity::prelude::*;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
+
+use crate::sources::{Source, TrackDescriptor};
+
+/* Library container */
+pub struct LibraryContainer;
+
+impl TypeMapKey for LibraryContainer {
+    type Value = Arc<RwLock<Library>>;
+}
+
+// Files the scan picks up, anything lofty can read tags from
+const EXTENSIONS: [&str; 8] = ["mp3", "flac", "ogg", "opus", "m4a", "aac", "wav", "wma"];
+
+// Local music directory, set with MUSIC_DIR. The library is off without it.
+pub fn music_dir() -> Option<PathBuf> {
+    env::var("MUSIC_DIR")
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+// Whether the path is a file inside the music directory, nothing else gets played
+// straight from disk
+pub fn is_local(path: &str) -> bool {
+    let dir = match music_dir().and_then(|dir| dir.canonicalize().ok()) {
+        Some(dir) => dir,
+        None => return false,
+    };
+    match Path::new(path).canonicalize() {
+        Ok(path) => path.starts_with(dir) && path.is_file(),
+        Err(_) => false,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LibraryTrack {
+    pub path: PathBuf,
+    pub title: String,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub track_number: Option<u32>,
+    pub duration: Option<Duration>,
+}
+
+impl LibraryTrack {
+    // Tags where there are some, the file name where there aren't
+    fn read(path: PathBuf) -> Self {
+        let file_name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let tagged = match lofty::read_from_path(&path) {
+            Ok(tagged) => tagged,
+            Err(why) => {
+                error!("Err reading tags of {}: {:?}", path.display(), why);
+                return LibraryTrack {
+                    path,
+                    title: file_name,
+                    artist: None,
+                    album: None,
+                    track_number: None,
+                    duration: None,
+                };
+            }
+        };
+
+        let duration = Some(tagged.properties().duration()).filter(|d| !d.is_zero());
+        let tag = tagged.primary_tag().or_else(|| tagged.first_tag());
+
+        LibraryTrack {
+            title: tag
+                .and_then(|tag| tag.title().map(|title| title.to_string()))
+                .unwrap_or(file_name),
+            artist: tag.and_then(|tag| tag.artist().map(|artist| artist.to_string())),
+            album: tag.and_then(|tag| tag.album().map(|album| album.to_string())),
+            track_number: tag.and_then(|tag| tag.track()),
+            duration,
+            path,
+        }
+    }
+
+    fn matches(&self, query: &Query) -> bool {
+        let contains = |field: &Option<String>, value: &str| {
+            field
+                .as_ref()
+                .map(|field| field.to_lowercase().contains(value))
+                .unwrap_or(false)
+        };
+        let file_name = self.path.to_string_lossy().to_lowercase();
+
+        query
+            .artist
+            .iter()
+            .all(|artist| contains(&self.artist, artist))
+            && query.album.iter().all(|album| contains(&self.album, album))
+            && query
+                .title
+                .iter()
+                .all(|title| self.title.to_lowercase().contains(title.as_str()))
+            && query.words.iter().all(|word| {
+                self.title.to_lowercase().contains(word.as_str())
+                    || contains(&self.artist, word)
+                    || contains(&self.album, word)
+                    || file_name.contains(word.as_str())
+            })
+    }
+
+    // The file through ffmpeg, with the tags as its metadata
+    pub fn descriptor(&self) -> TrackDescriptor {
+        let mut track =
+            TrackDescriptor::new(Source::Ffmpeg(self.path.to_string_lossy().to_string()));
+        track.title = Some(self.title.clone());
+        track.artist = self.artist.clone();
+        track.duration = self.duration;
+        track
+    }
+}
+
+// What local searches for: artist:, album: and title: narrow it down, every other
+// word has to show up somewhere in the tags or the path
+#[derive(Debug, Default)]
+pub struct Query {
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub title: Option<String>,
+    pub words: Vec<String>,
+}
+
+impl Query {
+    // "artist:pink floyd album:the wall" keeps the words after a key with that key
+    pub fn parse(input: &str) -> Self {
+        let mut query = Query::default();
+        let mut key = None;
+
+        for word in input.to_lowercase().split_whitespace() {
+            if let Some((name, value)) = word.split_once(':') {
+                if ["artist", "album", "title"].contains(&name) {
+                    key = Some(name.to_string());
+                    *query.field(name) = Some(value.to_string()).filter(|v| !v.is_empty());
+                    continue;
+                }
+            }
+
+            match &key {
+                Some(name) => {
+                    let field = query.field(name).get_or_insert_with(String::new);
+                    if !field.is_empty() {
+                        field.push(' ');
+                    }
+                    field.push_str(word);
+                }
+                None => query.words.push(word.to_string()),
+            }
+        }
+        query
+    }
+
+    fn field(&mut self, name: &str) -> &mut Option<String> {
+        match name {
+            "artist" => &mut self.artist,
+            "album" => &mut self.album,
+            _ => &mut self.title,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.artist.is_none()
+            && self.album.is_none()
+            && self.title.is_none()
+            && self.words.is_empty()
+    }
+}
+
+#[derive(Default)]
+pub struct Library {
+    pub tracks: Vec<LibraryTrack>,
+}
+
+impl Library {
+    // Reads the tags of every audio file under dir, blocking, so run it off the runtime
+    pub fn scan(dir: &Path) -> Self {
+        let mut paths = Vec::new();
+        let mut dirs = vec![dir.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(why) => {
+                    error!("Err reading {}: {:?}", dir.display(), why);
+                    continue;
+                }
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                // Symlinked folders aren't followed, one pointing back up would never end
+                let is_dir = entry
+                    .file_type()
+                    .map(|file_type| file_type.is_dir())
+                    .unwrap_or(false);
+                if is_dir {
+                    dirs.push(path);
+                } else if path
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .map(|extension| EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+                    .unwrap_or(false)
+                {
+                    paths.push(path);
+                }
+            }
+        }
+
+        let mut tracks = paths
+            .into_iter()
+            .map(LibraryTrack::read)
+            .collect::<Vec<_>>();
+        tracks.sort_by(|a, b| {
+            (&a.artist, &a.album, a.track_number, &a.title).cmp(&(
+                &b.artist,
+                &b.album,
+                b.track_number,
+                &b.title,
+            ))
+        });
+
+        Library { tracks }
+    }
+
+    // Matches in artist, album and track order
+    pub fn search(&self, query: &Query) -> Vec<LibraryTrack> {
+        self.tracks
+            .iter()
+            .filter(|track| track.matches(query))
+            .cloned()
+            .collect()
+    }
+}
+
+// Scans the music directory in the background and swaps the index in when it's done
+pub async fn rescan(data: &RwLock<TypeMap>) -> Option<usize> {
+    let dir = music_dir()?;
+
+    let library = {
+        let data = data.read().await;
+        data.get::<LibraryContainer>()
+            .expect("Library placed in at initialisation.")
+            .clone()
+    };
+
+    info!("Scanning music library at {}", dir.display());
+    let scanned = match tokio::task::spawn_blocking(move || Library::scan(&dir)).await {
+        Ok(scanned) => scanned,
+        Err(why) => {
+            error!("Music library scan failed: {:?}", why);
+            return None;
+        }
+    };
+
+    let count = scanned.tracks.len();
+    info!("Music library has {} tracks", count);
+    *library.write().await = scanned;
+    Some(count)
+}
@@ -1,18 +1,28 @@
 mod commands;
+mod library;
+mod player;
+mod playlist_files;
+mod playlists;
+mod settings;
+mod sources;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use serenity::async_trait;
 use serenity::client::bridge::gateway::ShardManager;
 use serenity::framework::standard::macros::{group, hook};
-use serenity::framework::standard::StandardFramework;
+use serenity::framework::standard::{CommandResult, DispatchError, Reason, StandardFramework};
 use serenity::http::Http;
 use serenity::model::channel::Message;
 use serenity::model::event::ResumedEvent;
 use serenity::model::gateway::Ready;
+use serenity::model::id::GuildId;
 use serenity::model::prelude::Activity;
+use serenity::model::voice::VoiceState;
+use serenity::model::Timestamp;
 use serenity::prelude::*;
 use tracing::{debug, error, info, instrument};
 
@@ -24,16 +34,40 @@ use crate::commands::help::*;
 use crate::commands::roll::*;
 
 use crate::commands::music::clear::*;
+use crate::commands::music::dj::*;
+use crate::commands::music::export::*;
+use crate::commands::music::ff::*;
+use crate::commands::music::history::*;
 use crate::commands::music::join::*;
 use crate::commands::music::leave::*;
+use crate::commands::music::local::*;
+use crate::commands::music::loop_mode::*;
+use crate::commands::music::move_track::*;
 use crate::commands::music::nowplaying::*;
 use crate::commands::music::pause::*;
 use crate::commands::music::play::*;
+use crate::commands::music::playlist::*;
+use crate::commands::music::playnext::*;
+use crate::commands::music::playnow::*;
+use crate::commands::music::previous::*;
 use crate::commands::music::queue::*;
+use crate::commands::music::remove::*;
 use crate::commands::music::resume::*;
+use crate::commands::music::rewind::*;
+use crate::commands::music::search::*;
+use crate::commands::music::seek::*;
 use crate::commands::music::shuffle::*;
 use crate::commands::music::skip::*;
+use crate::commands::music::skip_ratio::*;
 use crate::commands::music::stop::*;
+use crate::commands::music::volume::*;
+
+use crate::library::{rescan, Library, LibraryContainer};
+use crate::player::idle::refresh_idle;
+use crate::player::persist::{autosave, restore_all, save_queue};
+use crate::player::state::{with_guild_state, GuildStateContainer};
+use crate::playlists::{Playlists, PlaylistsContainer};
+use crate::settings::{Settings, SettingsContainer};
 
 /* Shards container */
 pub struct ShardManagerContainer;
@@ -42,7 +76,10 @@ impl TypeMapKey for ShardManagerContainer {
     type Value = Arc<Mutex<ShardManager>>;
 }
 
-struct Handler;
+struct Handler {
+    // cache_ready can fire again after reconnecting, queues only come back once
+    restored: AtomicBool,
+}
 
 #[async_trait]
 impl EventHandler for Handler {
@@ -56,22 +93,89 @@ impl EventHandler for Handler {
         ctx.set_activity(Activity::playing(status)).await;
     }
 
+    // Guilds and voice states are known now, so saved queues can come back
+    async fn cache_ready(&self, ctx: Context, _guilds: Vec<GuildId>) {
+        if self.restored.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        restore_all(&ctx).await;
+        tokio::spawn(autosave(ctx));
+    }
+
     #[instrument(skip(self, _ctx))]
     async fn resume(&self, _ctx: Context, resume: ResumedEvent) {
         debug!("Resumed; trace: {:?}", resume.trace);
     }
+
+    // Someone joining or leaving can make the bot idle in its voice channel or wake it up
+    async fn voice_state_update(&self, ctx: Context, _old: Option<VoiceState>, new: VoiceState) {
+        let guild_id = match new.guild_id {
+            Some(guild_id) => guild_id,
+            None => return,
+        };
+
+        let manager = songbird::get(&ctx)
+            .await
+            .expect("Songbird Voice client placed in at initialisation.")
+            .clone();
+
+        if manager.get(guild_id).is_some() {
+            refresh_idle(&ctx, guild_id, &[]).await;
+        }
+    }
 }
 
 #[hook]
-#[instrument]
-async fn before(_: &Context, msg: &Message, command_name: &str) -> bool {
+#[instrument(skip(ctx))]
+async fn before(ctx: &Context, msg: &Message, command_name: &str) -> bool {
     info!(
         "Received command --> '{}' || User --> '{}'",
         command_name, msg.author.name
     );
+
+    // Remember where to post notes like leaving the voice channel
+    if let Some(guild_id) = msg.guild_id {
+        with_guild_state(&ctx.data, guild_id, |state| {
+            state.last_channel = Some(msg.channel_id)
+        })
+        .await;
+    }
     true
 }
 
+// Commands can change the queue, keep the saved copy up to date
+#[hook]
+async fn after(ctx: &Context, msg: &Message, _: &str, _: CommandResult) {
+    if let Some(guild_id) = msg.guild_id {
+        save_queue(ctx, guild_id).await;
+    }
+}
+
+// Tell the user why a check stopped their command
+#[hook]
+async fn dispatch_error(ctx: &Context, msg: &Message, error: DispatchError, command_name: &str) {
+    if let DispatchError::CheckFailed(check, reason) = error {
+        info!(
+            "Check '{}' failed for command --> '{}' || User --> '{}'",
+            check, command_name, msg.author.name
+        );
+
+        if let Reason::User(reason) = reason {
+            let _ = msg
+                .channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(format!(":warning: {}", reason))
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await;
+        }
+    }
+}
+
 #[group]
 #[commands(
     // Misc
@@ -80,7 +184,10 @@ async fn before(_: &Context, msg: &Message, command_name: &str) -> bool {
     // Music commands
     leave,  play,   pause,  resume,  clear,
     skip,   stop,   queue,  shuffle, nowplaying,
-    join,
+    join,   seek,   ff,     rewind,  volume,
+    loop_mode, remove, move_track, history, previous,
+    skipratio, dj,   playlist, export, local, search,
+    playnext, playnow,
 
 )]
 struct General;
@@ -110,6 +217,8 @@ async fn main() {
     let framework = StandardFramework::new()
         .configure(|c| c.owners(owners).prefix(prefix))
         .before(before)
+        .after(after)
+        .on_dispatch_error(dispatch_error)
         .group(&GENERAL_GROUP);
 
     let intents = GatewayIntents::non_privileged()
@@ -120,15 +229,27 @@ async fn main() {
     let mut client = Client::builder(&token, intents)
         .framework(framework)
         .register_songbird()
-        .event_handler(Handler)
+        .event_handler(Handler {
+            restored: AtomicBool::new(false),
+        })
         .await
         .expect("Err creating client");
 
     {
         let mut data = client.data.write().await;
         data.insert::<ShardManagerContainer>(client.shard_manager.clone());
+        data.insert::<SettingsContainer>(Arc::new(RwLock::new(Settings::load())));
+        data.insert::<PlaylistsContainer>(Arc::new(RwLock::new(Playlists::load())));
+        data.insert::<GuildStateContainer>(Arc::new(Mutex::new(HashMap::new())));
+        data.insert::<LibraryContainer>(Arc::new(RwLock::new(Library::default())));
     }
 
+    // Reading the tags of a big library takes a while, the bot doesn't have to wait
+    let data = client.data.clone();
+    tokio::spawn(async move {
+        rescan(&data).await;
+    });
+
     let shard_manager = client.shard_manager.clone();
 
     tokio::spawn(async move {
@@ -0,0 +1,207 @@
+use serenity::async_trait;
+use serenity::model::id::GuildId;
+use serenity::model::Timestamp;
+use serenity::prelude::*;
+use songbird::events::{Event, EventContext, EventHandler as VoiceEventHandler, TrackEvent};
+use songbird::input::Metadata;
+use songbird::{Call, Songbird};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use crate::player::idle::refresh_idle;
+use crate::player::persist::save_queue;
+use crate::player::state::{with_guild_state, HistoryEntry};
+use crate::player::{enqueue, is_attachment, source_from_url, Discarded, RequestedBy};
+use crate::settings::{guild_settings, LoopMode};
+use crate::sources::lazy::take_failure;
+use crate::sources::web_url;
+
+// How many upcoming tracks get fetched ahead of time
+pub const PRELOAD_TRACKS: usize = 2;
+
+// Hooks into every track of the guild, needs to run whenever the bot joins a voice channel
+pub fn register_events(
+    ctx: &Context,
+    manager: Arc<Songbird>,
+    guild_id: GuildId,
+    handler: &mut Call,
+) {
+    // joining again reuses the same call, so don't stack handlers
+    handler.remove_all_global_events();
+
+    handler.add_global_event(
+        Event::Track(TrackEvent::Play),
+        TrackStart {
+            ctx: ctx.clone(),
+            guild_id,
+        },
+    );
+    handler.add_global_event(
+        Event::Track(TrackEvent::End),
+        TrackEnd {
+            ctx: ctx.clone(),
+            manager,
+            guild_id,
+        },
+    );
+}
+
+struct TrackStart {
+    ctx: Context,
+    guild_id: GuildId,
+}
+
+#[async_trait]
+impl VoiceEventHandler for TrackStart {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if let EventContext::Track(tracks) = ctx {
+            let settings = guild_settings(&self.ctx.data, self.guild_id).await;
+            if settings.loop_mode == LoopMode::Track {
+                for (_, handle) in tracks.iter() {
+                    let _ = handle.enable_loop();
+                }
+            }
+        }
+        preload(&self.ctx, self.guild_id).await;
+        refresh_idle(&self.ctx, self.guild_id, &[]).await;
+        save_queue(&self.ctx, self.guild_id).await;
+        None
+    }
+}
+
+// Playlist tracks are only fetched right before they play, get the next few
+// ready so there's no gap between them
+async fn preload(ctx: &Context, guild_id: GuildId) {
+    let manager = songbird::get(ctx)
+        .await
+        .expect("Songbird Voice client placed in at initialisation.")
+        .clone();
+
+    if let Some(handler_lock) = manager.get(guild_id) {
+        let handler = handler_lock.lock().await;
+        for track in handler
+            .queue()
+            .current_queue()
+            .iter()
+            .skip(1)
+            .take(PRELOAD_TRACKS)
+        {
+            let _ = track.make_playable();
+        }
+    }
+}
+
+struct TrackEnd {
+    ctx: Context,
+    manager: Arc<Songbird>,
+    guild_id: GuildId,
+}
+
+#[async_trait]
+impl VoiceEventHandler for TrackEnd {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if let EventContext::Track(tracks) = ctx {
+            let settings = guild_settings(&self.ctx.data, self.guild_id).await;
+
+            for (_, handle) in tracks.iter() {
+                let metadata = handle.metadata();
+                // Playlist tracks are fetched late, so they can fail long after being queued
+                let failure = take_failure(handle.uuid());
+
+                // Tracks thrown out by a command were never really played
+                let (discarded, requester) = {
+                    let typemap = handle.typemap().read().await;
+                    (
+                        typemap.get::<Discarded>().is_some(),
+                        typemap.get::<RequestedBy>().copied(),
+                    )
+                };
+                if discarded {
+                    continue;
+                }
+
+                if let Some(reason) = failure {
+                    report_failure(&self.ctx, self.guild_id, metadata, reason).await;
+                    continue;
+                }
+
+                let entry = HistoryEntry {
+                    title: metadata
+                        .title
+                        .clone()
+                        .unwrap_or_else(|| String::from("Unknown")),
+                    source_url: metadata.source_url.clone(),
+                    requester,
+                    played_at: Timestamp::now(),
+                };
+                with_guild_state(&self.ctx.data, self.guild_id, |state| {
+                    state.push_history(entry)
+                })
+                .await;
+
+                if settings.loop_mode != LoopMode::Queue {
+                    continue;
+                }
+
+                // A finished track can't be restarted, so fetch it again and put it at the back
+                let (url, requester) = match (metadata.source_url.clone(), requester) {
+                    (Some(url), Some(requester)) if !is_attachment(&url) => (url, requester),
+                    _ => continue,
+                };
+
+                let manager = self.manager.clone();
+                let guild_id = self.guild_id;
+                let settings = settings.clone();
+                tokio::spawn(async move {
+                    info!("Looping queue, requeueing --> {}", url);
+                    match source_from_url(&url).await {
+                        Ok(source) => {
+                            if let Some(handler_lock) = manager.get(guild_id) {
+                                let mut handler = handler_lock.lock().await;
+                                enqueue(&mut handler, source, &settings, requester).await;
+                            }
+                        }
+                        Err(why) => error!("Error requeueing '{}': {:?}", url, why),
+                    }
+                });
+            }
+
+            // The queue might be empty now, start counting down to leaving
+            let ended = tracks.iter().map(|(_, handle)| *handle).collect::<Vec<_>>();
+            refresh_idle(&self.ctx, self.guild_id, &ended).await;
+            save_queue(&self.ctx, self.guild_id).await;
+        }
+        None
+    }
+}
+
+// Tells the channel about a queued track that couldn't be played when its turn came
+async fn report_failure(ctx: &Context, guild_id: GuildId, metadata: &Metadata, reason: String) {
+    let channel_id = match with_guild_state(&ctx.data, guild_id, |state| state.last_channel).await {
+        Some(channel_id) => channel_id,
+        None => return,
+    };
+
+    let title = metadata
+        .title
+        .clone()
+        .unwrap_or_else(|| String::from("Unknown"));
+    let description = match metadata.source_url.as_deref().and_then(web_url) {
+        Some(url) => format!("[{title}]({url}) - {reason}"),
+        None => format!("{title} - {reason}"),
+    };
+
+    if let Err(why) = channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(0xf38ba8)
+                    .title(":warning: Couldn't play a song")
+                    .description(description)
+                    .timestamp(Timestamp::now())
+            })
+        })
+        .await
+    {
+        error!("Err reporting failed track: {:?}", why);
+    }
+}
@@ -0,0 +1,147 @@
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use songbird::tracks::TrackHandle;
+use std::env;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+use crate::player::listeners;
+use crate::player::state::with_guild_state;
+
+// How long the bot waits after the last listener left the channel
+const ALONE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdleReason {
+    // Nothing is queued
+    EmptyQueue,
+    // No humans left in the voice channel
+    Alone,
+}
+
+pub struct IdleTimer {
+    reason: IdleReason,
+    task: JoinHandle<()>,
+}
+
+// How long the bot stays in a voice channel with nothing queued, set in minutes
+// with IDLE_TIMEOUT
+fn idle_timeout() -> Duration {
+    let minutes: u64 = env::var("IDLE_TIMEOUT")
+        .ok()
+        .and_then(|minutes| minutes.parse().ok())
+        .unwrap_or(5);
+    Duration::from_secs(minutes.saturating_mul(60))
+}
+
+// Why the bot should leave the guild's voice channel, if it should. Tracks in
+// `ended` have just finished and don't count as queued anymore.
+async fn idle_reason(
+    ctx: &Context,
+    guild_id: GuildId,
+    ended: &[&TrackHandle],
+) -> Option<IdleReason> {
+    let manager = songbird::get(ctx)
+        .await
+        .expect("Songbird Voice client placed in at initialisation.")
+        .clone();
+    let handler_lock = manager.get(guild_id)?;
+
+    if let Some(guild) = ctx.cache.guild(guild_id) {
+        if listeners(ctx, &guild).is_empty() {
+            return Some(IdleReason::Alone);
+        }
+    }
+
+    let queue = handler_lock.lock().await.queue().current_queue();
+    let empty = queue
+        .iter()
+        .all(|track| ended.iter().any(|ended| ended.uuid() == track.uuid()));
+    match empty {
+        true => Some(IdleReason::EmptyQueue),
+        false => None,
+    }
+}
+
+// Starts, keeps or stops the guild's idle timer depending on whether the bot is idle
+pub async fn refresh_idle(ctx: &Context, guild_id: GuildId, ended: &[&TrackHandle]) {
+    let reason = idle_reason(ctx, guild_id, ended).await;
+
+    with_guild_state(&ctx.data, guild_id, |state| {
+        // a timer for the same reason is already counting down
+        if let (Some(timer), Some(reason)) = (&state.idle_timer, reason) {
+            if timer.reason == reason {
+                return;
+            }
+        }
+
+        if let Some(timer) = state.idle_timer.take() {
+            timer.task.abort();
+        }
+
+        if let Some(reason) = reason {
+            let delay = match reason {
+                IdleReason::EmptyQueue => idle_timeout(),
+                IdleReason::Alone => ALONE_TIMEOUT,
+            };
+
+            let ctx = ctx.clone();
+            let task = tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                leave_if_idle(&ctx, guild_id).await;
+            });
+            state.idle_timer = Some(IdleTimer { reason, task });
+        }
+    })
+    .await;
+}
+
+async fn leave_if_idle(ctx: &Context, guild_id: GuildId) {
+    // the timer is done either way, drop it without aborting this very task
+    let last_channel = with_guild_state(&ctx.data, guild_id, |state| {
+        state.idle_timer = None;
+        state.last_channel
+    })
+    .await;
+
+    let reason = match idle_reason(ctx, guild_id, &[]).await {
+        Some(reason) => reason,
+        None => return,
+    };
+
+    let manager = songbird::get(ctx)
+        .await
+        .expect("Songbird Voice client placed in at initialisation.")
+        .clone();
+
+    info!(
+        "Leaving voice channel in guild {} --> {:?}",
+        guild_id, reason
+    );
+    if let Err(why) = manager.remove(guild_id).await {
+        error!("Failed to leave voice channel: {:?}", why);
+        return;
+    }
+
+    let description = match reason {
+        IdleReason::EmptyQueue => format!(
+            "Nothing was queued for {} minutes.",
+            idle_timeout().as_secs() / 60
+        ),
+        IdleReason::Alone => String::from("Everyone left the voice channel."),
+    };
+
+    if let Some(channel_id) = last_channel {
+        let _ = channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xffffff)
+                        .title("Left voice channel!")
+                        .description(description)
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await;
+    }
+}
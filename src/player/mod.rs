@@ -0,0 +1,257 @@
+pub mod events;
+pub mod idle;
+pub mod permissions;
+pub mod persist;
+pub mod state;
+
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use songbird::tracks::{create_player_with_uuid, LoopState, TrackHandle};
+use songbird::Call;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::player::events::register_events;
+use crate::player::persist::restore_queue;
+use crate::settings::{GuildSettings, LoopMode};
+use crate::sources::{web_url, Playable, ResolveError, TrackDescriptor};
+
+// Who queued a track, kept in the track handle's typemap
+pub struct RequestedBy;
+
+impl TypeMapKey for RequestedBy {
+    type Value = UserId;
+}
+
+// Users who voted to skip a track. Kept on the track itself, so the votes are
+// gone as soon as the next track starts.
+pub struct SkipVotes;
+
+impl TypeMapKey for SkipVotes {
+    type Value = HashSet<UserId>;
+}
+
+// Humans in the voice channel the bot is connected to
+pub fn listeners(ctx: &Context, guild: &Guild) -> Vec<UserId> {
+    let bot_id = ctx.cache.current_user_id();
+    match guild
+        .voice_states
+        .get(&bot_id)
+        .and_then(|voice_state| voice_state.channel_id)
+    {
+        Some(channel_id) => channel_listeners(ctx, guild, channel_id),
+        None => Vec::new(),
+    }
+}
+
+// Humans in a voice channel, whether the bot is there or not
+pub fn channel_listeners(ctx: &Context, guild: &Guild, channel_id: ChannelId) -> Vec<UserId> {
+    let bot_id = ctx.cache.current_user_id();
+    guild
+        .voice_states
+        .values()
+        .filter(|voice_state| voice_state.channel_id == Some(channel_id))
+        .filter(|voice_state| {
+            let bot = match &voice_state.member {
+                Some(member) => member.user.bot,
+                None => ctx
+                    .cache
+                    .user(voice_state.user_id)
+                    .map(|user| user.bot)
+                    .unwrap_or(false),
+            };
+            !bot && voice_state.user_id != bot_id
+        })
+        .map(|voice_state| voice_state.user_id)
+        .collect()
+}
+
+// The guild's call, joining the author's voice channel first if the bot isn't in one.
+// None means the author was told why that didn't work.
+pub async fn join_author(
+    ctx: &Context,
+    msg: &Message,
+    guild: &Guild,
+) -> serenity::Result<Option<Arc<Mutex<Call>>>> {
+    let manager = songbird::get(ctx)
+        .await
+        .expect("Songbird Voice client placed in at initialisation.")
+        .clone();
+
+    if let Some(handler_lock) = manager.get(guild.id) {
+        return Ok(Some(handler_lock));
+    }
+
+    let connect_to = match guild
+        .voice_states
+        .get(&msg.author.id)
+        .and_then(|voice_state| voice_state.channel_id)
+    {
+        Some(channel) => channel,
+        None => {
+            msg.channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xf38ba8)
+                            .title(":warning: Join a voice channel first!")
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await?;
+            return Ok(None);
+        }
+    };
+
+    let (handler_lock, success) = manager.join(guild.id, connect_to).await;
+
+    if let Err(_channel) = success {
+        msg.channel_id
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.colour(0xf38ba8)
+                        .title(":warning: error joining channel.")
+                        .description("Please ensure I have the correct permissions.")
+                        .timestamp(Timestamp::now())
+                })
+            })
+            .await?;
+        return Ok(None);
+    }
+
+    {
+        let mut handler = handler_lock.lock().await;
+        register_events(ctx, manager.clone(), guild.id, &mut handler);
+    }
+
+    // Bring back whatever was queued before the bot restarted
+    restore_queue(ctx, guild.id).await;
+
+    Ok(Some(handler_lock))
+}
+
+// Fetches a track again from its source url
+pub async fn source_from_url(url: &str) -> Result<Playable, ResolveError> {
+    TrackDescriptor::from_url(url).input().await
+}
+
+// Discord attachment links expire after a while, tracks played from one can't be
+// fetched again later
+pub fn is_attachment(url: &str) -> bool {
+    web_url(url)
+        .map(|url| {
+            matches!(
+                url.host_str(),
+                Some("cdn.discordapp.com" | "media.discordapp.net")
+            ) && url.path().starts_with("/attachments/")
+        })
+        .unwrap_or(false)
+}
+
+// Seconds of audio left across the queue, live streams don't count
+pub fn total_time(queue: &[TrackHandle]) -> u64 {
+    queue
+        .iter()
+        .filter_map(|track| track.metadata().duration)
+        .map(|duration| duration.as_secs())
+        .sum()
+}
+
+// Every track goes through here so the guild's playback settings apply to it
+pub async fn enqueue(
+    handler: &mut Call,
+    source: Playable,
+    settings: &GuildSettings,
+    requester: UserId,
+) -> TrackHandle {
+    let (mut track, handle) = create_player_with_uuid(source.input, source.uuid);
+    track.set_volume(settings.volume());
+    handle
+        .typemap()
+        .write()
+        .await
+        .insert::<RequestedBy>(requester);
+
+    // a track added to an empty queue starts without a play event, so loop it here
+    if settings.loop_mode == LoopMode::Track && handler.queue().is_empty() {
+        let _ = track.set_loops(LoopState::Infinite);
+    }
+
+    handler.enqueue(track);
+    handle
+}
+
+// Starts a queued track right away. The current track is paused and kept right
+// behind it, so it picks up where it left off once the new track is done.
+pub fn play_first(handler: &Call, track: &TrackHandle) {
+    handler.queue().modify_queue(|queue| {
+        let index = match queue
+            .iter()
+            .position(|queued| queued.uuid() == track.uuid())
+        {
+            Some(index) => index,
+            None => return,
+        };
+        if index == 0 {
+            return;
+        }
+
+        if let Some(queued) = queue.remove(index) {
+            if let Some(current) = queue.front() {
+                let _ = current.pause();
+            }
+            let _ = queued.play();
+            queue.push_front(queued);
+        }
+    });
+}
+
+// Moves a track that was just queued up to right behind `after`, or right behind
+// the current track. A track queued into an empty queue is already playing.
+pub fn queue_next(handler: &Call, track: &TrackHandle, after: Option<&TrackHandle>) {
+    handler.queue().modify_queue(|queue| {
+        let index = match queue
+            .iter()
+            .position(|queued| queued.uuid() == track.uuid())
+        {
+            Some(index) if index > 0 => index,
+            _ => return,
+        };
+        let position = after
+            .and_then(|after| {
+                queue
+                    .iter()
+                    .position(|queued| queued.uuid() == after.uuid())
+            })
+            .map(|position| position + 1)
+            .unwrap_or(1)
+            .min(index);
+
+        if let Some(queued) = queue.remove(index) {
+            queue.insert(position, queued);
+        }
+    });
+}
+
+// Marks a track as thrown away by a command, so the end event doesn't treat it
+// like a finished track
+pub struct Discarded;
+
+impl TypeMapKey for Discarded {
+    type Value = ();
+}
+
+// Stops a track that has been taken out of the queue
+pub async fn discard(track: &TrackHandle) {
+    track.typemap().write().await.insert::<Discarded>(());
+    let _ = track.stop();
+}
+
+// Stops and empties the queue
+pub async fn stop_queue(handler: &Call) {
+    let tracks = handler
+        .queue()
+        .modify_queue(|queue| queue.drain(..).collect::<Vec<_>>());
+    for track in tracks {
+        discard(&track).await;
+    }
+}
@@ -0,0 +1,109 @@
+use serenity::framework::standard::macros::check;
+use serenity::framework::standard::{Args, CommandOptions, Reason};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use songbird::tracks::TrackHandle;
+use tracing::error;
+
+use crate::player::RequestedBy;
+use crate::settings::guild_settings;
+
+// Server owners and members with Manage Guild
+pub async fn is_admin(ctx: &Context, guild: &Guild, user_id: UserId) -> bool {
+    match guild.member_permissions(ctx, user_id).await {
+        Ok(permissions) => permissions.manage_guild(),
+        Err(why) => {
+            error!("Err getting permissions of {}: {:?}", user_id, why);
+            false
+        }
+    }
+}
+
+// DJs can control the music for everyone: admins and members with the guild's DJ role
+pub async fn is_dj(ctx: &Context, guild: &Guild, user_id: UserId) -> bool {
+    if let Some(dj_role) = guild_settings(&ctx.data, guild.id).await.dj_role {
+        match guild.member(ctx, user_id).await {
+            Ok(member) if member.roles.contains(&dj_role) => return true,
+            Ok(_) => {}
+            Err(why) => error!("Err getting member {}: {:?}", user_id, why),
+        }
+    }
+    is_admin(ctx, guild, user_id).await
+}
+
+// In DJ-only mode nobody but DJs can change the queue
+pub async fn can_queue(ctx: &Context, guild: &Guild, user_id: UserId) -> bool {
+    !guild_settings(&ctx.data, guild.id).await.dj_only || is_dj(ctx, guild, user_id).await
+}
+
+// Whether every one of the tracks was queued by the user. An empty queue isn't
+// anyone's, so it only leaves DJs in charge.
+pub async fn requested_all(tracks: &[TrackHandle], user_id: UserId) -> bool {
+    if tracks.is_empty() {
+        return false;
+    }
+    for track in tracks {
+        if track.typemap().read().await.get::<RequestedBy>() != Some(&user_id) {
+            return false;
+        }
+    }
+    true
+}
+
+// For commands that change the whole queue: DJs, or whoever queued every track in it
+#[check]
+#[name = "DJ"]
+pub async fn dj_check(
+    ctx: &Context,
+    msg: &Message,
+    _: &mut Args,
+    _: &CommandOptions,
+) -> Result<(), Reason> {
+    let guild = match msg.guild(&ctx.cache) {
+        Some(guild) => guild,
+        None => return Ok(()),
+    };
+
+    if is_dj(ctx, &guild, msg.author.id).await {
+        return Ok(());
+    }
+
+    let manager = songbird::get(ctx)
+        .await
+        .expect("Songbird Voice client placed in at initialisation.")
+        .clone();
+
+    if let Some(handler_lock) = manager.get(guild.id) {
+        let tracks = handler_lock.lock().await.queue().current_queue();
+        if requested_all(&tracks, msg.author.id).await {
+            return Ok(());
+        }
+    }
+
+    Err(Reason::User(String::from(
+        "Only DJs or whoever queued the songs can do that.",
+    )))
+}
+
+// For commands that only change the queue
+#[check]
+#[name = "DjOnly"]
+pub async fn dj_only_check(
+    ctx: &Context,
+    msg: &Message,
+    _: &mut Args,
+    _: &CommandOptions,
+) -> Result<(), Reason> {
+    let guild = match msg.guild(&ctx.cache) {
+        Some(guild) => guild,
+        None => return Ok(()),
+    };
+
+    if can_queue(ctx, &guild, msg.author.id).await {
+        return Ok(());
+    }
+
+    Err(Reason::User(String::from(
+        "The queue is locked to DJs right now.",
+    )))
+}
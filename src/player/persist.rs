@@ -0,0 +1,313 @@
+use serde::{Deserialize, Serialize};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{error, info};
+
+use crate::player::events::register_events;
+use crate::player::state::{with_guild_state, GuildStateContainer};
+use crate::player::{
+    channel_listeners, enqueue, is_attachment, source_from_url, Discarded, RequestedBy,
+};
+use crate::settings::{data_dir, guild_settings};
+
+// How often the position in the current track gets written down
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+// A guild's queue as it's kept on disk, loop mode and volume live in the settings
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedQueue {
+    // Voice channel the bot was playing in
+    pub channel_id: ChannelId,
+    // Where the last command came from
+    pub text_channel: Option<ChannelId>,
+    // Seconds into the first track
+    pub position: u64,
+    pub tracks: Vec<SavedTrack>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedTrack {
+    pub url: String,
+    pub title: Option<String>,
+    pub requester: UserId,
+}
+
+fn queue_path(guild_id: GuildId) -> PathBuf {
+    data_dir().join("queues").join(format!("{}.json", guild_id))
+}
+
+async fn load_queue(guild_id: GuildId) -> Option<SavedQueue> {
+    let path = queue_path(guild_id);
+    let raw = tokio::fs::read_to_string(&path).await.ok()?;
+    match serde_json::from_str(&raw) {
+        Ok(saved) => Some(saved),
+        Err(why) => {
+            error!("Err parsing {}: {:?}", path.display(), why);
+            None
+        }
+    }
+}
+
+async fn write_queue(guild_id: GuildId, saved: &SavedQueue) -> std::io::Result<()> {
+    let path = queue_path(guild_id);
+    let raw = serde_json::to_string_pretty(saved)?;
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+
+    // write to a temporary file first so a crash can't leave half a file behind
+    let tmp = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp, raw).await?;
+    tokio::fs::rename(&tmp, &path).await
+}
+
+// Drops the guild's saved queue, for when the music is stopped on purpose
+pub async fn forget_queue(guild_id: GuildId) {
+    let path = queue_path(guild_id);
+    if let Err(why) = tokio::fs::remove_file(&path).await {
+        if why.kind() != std::io::ErrorKind::NotFound {
+            error!("Err removing {}: {:?}", path.display(), why);
+        }
+    }
+}
+
+// Writes the guild's current queue to disk, an empty queue removes the saved one
+pub async fn save_queue(ctx: &Context, guild_id: GuildId) {
+    // half a restored queue would overwrite the full one
+    let (restoring, text_channel) = with_guild_state(&ctx.data, guild_id, |state| {
+        (state.restoring, state.last_channel)
+    })
+    .await;
+    if restoring {
+        return;
+    }
+
+    let manager = songbird::get(ctx)
+        .await
+        .expect("Songbird Voice client placed in at initialisation.")
+        .clone();
+
+    let handler_lock = match manager.get(guild_id) {
+        Some(handler_lock) => handler_lock,
+        None => return,
+    };
+
+    let bot_id = ctx.cache.current_user_id();
+    let channel_id = match ctx.cache.guild(guild_id).and_then(|guild| {
+        guild
+            .voice_states
+            .get(&bot_id)
+            .and_then(|voice_state| voice_state.channel_id)
+    }) {
+        Some(channel_id) => channel_id,
+        None => return,
+    };
+
+    let queue = handler_lock.lock().await.queue().current_queue();
+
+    let mut position = None;
+    let mut tracks = Vec::new();
+    for handle in queue.iter() {
+        // finished tracks can linger in the queue for a moment
+        let info = match handle.get_info().await {
+            Ok(info) => info,
+            Err(_) => continue,
+        };
+        let requester = {
+            let typemap = handle.typemap().read().await;
+            if typemap.get::<Discarded>().is_some() {
+                continue;
+            }
+            typemap.get::<RequestedBy>().copied()
+        };
+
+        let metadata = handle.metadata();
+        let url = metadata
+            .source_url
+            .clone()
+            .filter(|url| !is_attachment(url));
+        if let (Some(url), Some(requester)) = (url, requester) {
+            position.get_or_insert(info.position.as_secs());
+            tracks.push(SavedTrack {
+                url,
+                title: metadata.title.clone(),
+                requester,
+            });
+        }
+    }
+
+    if tracks.is_empty() {
+        forget_queue(guild_id).await;
+        return;
+    }
+
+    let saved = SavedQueue {
+        channel_id,
+        text_channel,
+        position: position.unwrap_or(0),
+        tracks,
+    };
+    if let Err(why) = write_queue(guild_id, &saved).await {
+        error!("Failed to save queue of guild {}: {:?}", guild_id, why);
+    }
+}
+
+// Starts putting a saved queue back into the guild's call. Tracks are fetched one at
+// a time in the background, so the music starts long before a big queue is back.
+pub async fn restore_queue(ctx: &Context, guild_id: GuildId) {
+    let saved = match load_queue(guild_id).await {
+        Some(saved) => saved,
+        None => return,
+    };
+
+    let manager = songbird::get(ctx)
+        .await
+        .expect("Songbird Voice client placed in at initialisation.")
+        .clone();
+
+    // a call that still has its queue, like after being dragged to another channel,
+    // doesn't need it back
+    let empty = match manager.get(guild_id) {
+        Some(handler_lock) => handler_lock.lock().await.queue().is_empty(),
+        None => false,
+    };
+    if !empty {
+        return;
+    }
+
+    info!(
+        "Restoring {} tracks in guild {}",
+        saved.tracks.len(),
+        guild_id
+    );
+    with_guild_state(&ctx.data, guild_id, |state| {
+        state.restoring = true;
+        state.last_channel = state.last_channel.or(saved.text_channel);
+    })
+    .await;
+
+    let ctx = ctx.clone();
+    tokio::spawn(async move {
+        let settings = guild_settings(&ctx.data, guild_id).await;
+        let mut restored = 0;
+        for (i, track) in saved.tracks.iter().enumerate() {
+            let source = match source_from_url(&track.url).await {
+                Ok(source) => source,
+                Err(why) => {
+                    error!("Error restoring '{}': {:?}", track.url, why);
+                    continue;
+                }
+            };
+
+            // the bot might have been told to leave in the meantime
+            let handler_lock = match manager.get(guild_id) {
+                Some(handler_lock) => handler_lock,
+                None => break,
+            };
+            let mut handler = handler_lock.lock().await;
+            let handle = enqueue(&mut handler, source, &settings, track.requester).await;
+            if i == 0 && saved.position > 0 {
+                let _ = handle.seek_time(Duration::from_secs(saved.position));
+            }
+            restored += 1;
+        }
+
+        with_guild_state(&ctx.data, guild_id, |state| state.restoring = false).await;
+        save_queue(&ctx, guild_id).await;
+
+        if let Some(channel_id) = saved.text_channel {
+            let _ = channel_id
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.colour(0xffffff)
+                            .title(":recycle: Restored the queue!")
+                            .description(format!(
+                                "{} of {} songs are back from before the restart.",
+                                restored,
+                                saved.tracks.len()
+                            ))
+                            .timestamp(Timestamp::now())
+                    })
+                })
+                .await;
+        }
+    });
+}
+
+// Rejoins every voice channel that still has listeners in it and brings its queue
+// back. The others keep their saved queue until someone uses join or play.
+pub async fn restore_all(ctx: &Context) {
+    let mut entries = match tokio::fs::read_dir(data_dir().join("queues")).await {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    let manager = songbird::get(ctx)
+        .await
+        .expect("Songbird Voice client placed in at initialisation.")
+        .clone();
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        let guild_id = match path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(".json"))
+            .and_then(|id| id.parse::<u64>().ok())
+        {
+            Some(id) => GuildId(id),
+            None => continue,
+        };
+
+        let saved = match load_queue(guild_id).await {
+            Some(saved) => saved,
+            None => continue,
+        };
+
+        let listening = match ctx.cache.guild(guild_id) {
+            Some(guild) => !channel_listeners(ctx, &guild, saved.channel_id).is_empty(),
+            None => false,
+        };
+        if !listening {
+            info!(
+                "Nobody is listening in guild {}, keeping its queue for later",
+                guild_id
+            );
+            continue;
+        }
+
+        let (handler_lock, success) = manager.join(guild_id, saved.channel_id).await;
+        if let Err(why) = success {
+            error!("Failed to rejoin voice in guild {}: {:?}", guild_id, why);
+            continue;
+        }
+        {
+            let mut handler = handler_lock.lock().await;
+            register_events(ctx, manager.clone(), guild_id, &mut handler);
+        }
+
+        restore_queue(ctx, guild_id).await;
+    }
+}
+
+// Keeps the saved position in the current tracks fresh, in case the bot crashes
+pub async fn autosave(ctx: Context) {
+    let mut interval = tokio::time::interval(AUTOSAVE_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let states = {
+            let data = ctx.data.read().await;
+            data.get::<GuildStateContainer>()
+                .expect("Guild state placed in at initialisation.")
+                .clone()
+        };
+        let guild_ids = states.lock().await.keys().copied().collect::<Vec<_>>();
+
+        for guild_id in guild_ids {
+            save_queue(&ctx, guild_id).await;
+        }
+    }
+}
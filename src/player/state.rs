@@ -0,0 +1,61 @@
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use serenity::model::Timestamp;
+use serenity::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use crate::player::idle::IdleTimer;
+
+// How many played tracks are remembered per guild
+const HISTORY_LIMIT: usize = 50;
+
+/* Guild state container */
+pub struct GuildStateContainer;
+
+impl TypeMapKey for GuildStateContainer {
+    type Value = Arc<Mutex<HashMap<GuildId, GuildState>>>;
+}
+
+// Everything about a guild's music session that doesn't need to survive a restart
+#[derive(Default)]
+pub struct GuildState {
+    // Most recently played track first
+    pub history: VecDeque<HistoryEntry>,
+    // Where the last command came from, notes about the session go there
+    pub last_channel: Option<ChannelId>,
+    // Counts down to leaving the voice channel while nothing is happening
+    pub idle_timer: Option<IdleTimer>,
+    // A saved queue is being put back, don't save the half that's back yet
+    pub restoring: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct HistoryEntry {
+    pub title: String,
+    pub source_url: Option<String>,
+    pub requester: Option<UserId>,
+    pub played_at: Timestamp,
+}
+
+impl GuildState {
+    pub fn push_history(&mut self, entry: HistoryEntry) {
+        self.history.push_front(entry);
+        self.history.truncate(HISTORY_LIMIT);
+    }
+}
+
+// Runs f on the guild's state, creating it on first use
+pub async fn with_guild_state<F, R>(data: &RwLock<TypeMap>, guild_id: GuildId, f: F) -> R
+where
+    F: FnOnce(&mut GuildState) -> R,
+{
+    let states = {
+        let data = data.read().await;
+        data.get::<GuildStateContainer>()
+            .expect("Guild state placed in at initialisation.")
+            .clone()
+    };
+    let mut states = states.lock().await;
+    let state = states.entry(guild_id).or_default();
+    f(state)
+}
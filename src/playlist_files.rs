@@ -0,0 +1,306 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::playlists::PlaylistTrack;
+use crate::sources::{Source, TrackDescriptor};
+
+// Playlist files other tools understand
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    M3u,
+    Json,
+    Text,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name.to_lowercase().as_str() {
+            "m3u" | "m3u8" => Some(Format::M3u),
+            "json" => Some(Format::Json),
+            "txt" | "text" => Some(Format::Text),
+            _ => None,
+        }
+    }
+
+    // Picks the format by the file's extension
+    pub fn from_filename(filename: &str) -> Option<Format> {
+        Path::new(filename)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(Format::from_name)
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            // always written as UTF-8
+            Format::M3u => "m3u8",
+            Format::Json => "json",
+            Format::Text => "txt",
+        }
+    }
+}
+
+// One line of an imported playlist
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Entry {
+    Url(String),
+    // Anything that isn't a link gets looked up on YouTube
+    Search(String),
+}
+
+impl Entry {
+    fn parse(line: &str) -> Option<Entry> {
+        let line = line.trim();
+        if line.is_empty() {
+            return None;
+        }
+        match line.starts_with("http://") || line.starts_with("https://") {
+            true => Some(Entry::Url(line.to_string())),
+            false => Some(Entry::Search(line.to_string())),
+        }
+    }
+}
+
+impl From<Entry> for TrackDescriptor {
+    fn from(entry: Entry) -> Self {
+        match entry {
+            Entry::Url(url) => TrackDescriptor::from_url(&url),
+            Entry::Search(query) => TrackDescriptor::new(Source::Search(query)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonPlaylist {
+    tracks: Vec<JsonTrack>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonTrack {
+    Line(String),
+    Track {
+        url: Option<String>,
+        title: Option<String>,
+    },
+}
+
+// The shapes of JSON files we take: our own export, or a bare list of tracks
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonFile {
+    Playlist(JsonPlaylist),
+    Tracks(Vec<JsonTrack>),
+}
+
+// Where the tracks of a file point to, in order. Lines that can't be played are
+// left out.
+pub fn parse(raw: &str, format: Format) -> Result<Vec<Entry>, serde_json::Error> {
+    match format {
+        Format::M3u => Ok(parse_m3u(raw)),
+        Format::Text => Ok(raw
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .filter_map(Entry::parse)
+            .collect()),
+        Format::Json => {
+            let tracks = match serde_json::from_str(raw)? {
+                JsonFile::Playlist(playlist) => playlist.tracks,
+                JsonFile::Tracks(tracks) => tracks,
+            };
+            Ok(tracks
+                .into_iter()
+                .filter_map(|track| match track {
+                    JsonTrack::Line(line) => Entry::parse(&line),
+                    JsonTrack::Track { url: Some(url), .. } => Entry::parse(&url),
+                    JsonTrack::Track {
+                        title: Some(title), ..
+                    } => Entry::parse(&title),
+                    JsonTrack::Track { .. } => None,
+                })
+                .collect())
+        }
+    }
+}
+
+// Links are played as they are. Files from a local player can't be, so they're
+// searched for by the #EXTINF title or else the file's name.
+fn parse_m3u(raw: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut title = None;
+
+    for line in raw
+        .lines()
+        .map(|line| line.trim_start_matches('\u{feff}').trim())
+    {
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            title = info
+                .split_once(',')
+                .map(|(_, title)| title.trim().to_string())
+                .filter(|title| !title.is_empty());
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let entry = match Entry::parse(line) {
+            Some(Entry::Search(path)) => {
+                let name = title.take().or_else(|| {
+                    Path::new(&path.replace('\\', "/"))
+                        .file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .map(String::from)
+                });
+                name.map(Entry::Search)
+            }
+            entry => entry,
+        };
+        entries.extend(entry);
+        title = None;
+    }
+    entries
+}
+
+pub fn write(name: &str, tracks: &[PlaylistTrack], format: Format) -> String {
+    match format {
+        Format::M3u => {
+            let mut out = format!("#EXTM3U\n#PLAYLIST:{}\n", name);
+            for track in tracks {
+                let duration = track.duration.map(|secs| secs as i64).unwrap_or(-1);
+                let title = track.title.as_deref().unwrap_or("Unknown");
+                out.push_str(&format!("#EXTINF:{},{}\n{}\n", duration, title, track.url));
+            }
+            out
+        }
+        Format::Json => {
+            let playlist = ExportedPlaylist { name, tracks };
+            serde_json::to_string_pretty(&playlist).unwrap_or_default()
+        }
+        Format::Text => tracks
+            .iter()
+            .map(|track| format!("{}\n", track.url))
+            .collect(),
+    }
+}
+
+#[derive(Serialize)]
+struct ExportedPlaylist<'a> {
+    name: &'a str,
+    tracks: &'a [PlaylistTrack],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracks() -> Vec<PlaylistTrack> {
+        vec![
+            PlaylistTrack {
+                url: String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+                title: Some(String::from("Never Gonna Give You Up")),
+                duration: Some(213),
+            },
+            PlaylistTrack {
+                url: String::from("https://www.youtube.com/watch?v=jfKfPfyJRdk"),
+                title: None,
+                duration: None,
+            },
+        ]
+    }
+
+    fn urls() -> Vec<Entry> {
+        tracks()
+            .into_iter()
+            .map(|track| Entry::Url(track.url))
+            .collect()
+    }
+
+    #[test]
+    fn round_trips() {
+        for format in [Format::M3u, Format::Json, Format::Text] {
+            let written = write("mix", &tracks(), format);
+            assert_eq!(parse(&written, format).unwrap(), urls(), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn m3u_searches_files_by_title() {
+        let raw = "#EXTM3U\n\
+                   #EXTINF:213,Rick Astley - Never Gonna Give You Up\n\
+                   C:\\Music\\rick.mp3\n\
+                   #EXTINF:-1,\n\
+                   /home/me/Music/lofi beats.flac\n\
+                   https://example.com/stream.mp3\n";
+        assert_eq!(
+            parse(raw, Format::M3u).unwrap(),
+            vec![
+                Entry::Search(String::from("Rick Astley - Never Gonna Give You Up")),
+                Entry::Search(String::from("lofi beats")),
+                Entry::Url(String::from("https://example.com/stream.mp3")),
+            ]
+        );
+    }
+
+    #[test]
+    fn m3u_title_only_applies_to_the_next_entry() {
+        let raw = "#EXTINF:10,First\nhttps://example.com/a.mp3\nsong.mp3\n";
+        assert_eq!(
+            parse(raw, Format::M3u).unwrap(),
+            vec![
+                Entry::Url(String::from("https://example.com/a.mp3")),
+                Entry::Search(String::from("song")),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_blank_and_comment_lines() {
+        let raw =
+            "# my mix\n\n   \nhttps://example.com/a.mp3\n#https://example.com/b.mp3\nlofi beats\n";
+        let expected = vec![
+            Entry::Url(String::from("https://example.com/a.mp3")),
+            Entry::Search(String::from("lofi beats")),
+        ];
+        assert_eq!(parse(raw, Format::Text).unwrap(), expected);
+        assert_eq!(parse(raw, Format::M3u).unwrap()[0], expected[0]);
+    }
+
+    #[test]
+    fn reads_crlf() {
+        let raw = "\u{feff}#EXTM3U\r\n#EXTINF:5,Song\r\nsong.mp3\r\nhttps://example.com/a.mp3\r\n";
+        assert_eq!(
+            parse(raw, Format::M3u).unwrap(),
+            vec![
+                Entry::Search(String::from("Song")),
+                Entry::Url(String::from("https://example.com/a.mp3")),
+            ]
+        );
+        assert_eq!(
+            parse("https://example.com/a.mp3\r\nlofi\r\n", Format::Text).unwrap(),
+            vec![
+                Entry::Url(String::from("https://example.com/a.mp3")),
+                Entry::Search(String::from("lofi")),
+            ]
+        );
+    }
+
+    #[test]
+    fn json_takes_bare_lists() {
+        let raw = r#"["https://example.com/a.mp3", {"title": "lofi"}, {"url": null}]"#;
+        assert_eq!(
+            parse(raw, Format::Json).unwrap(),
+            vec![
+                Entry::Url(String::from("https://example.com/a.mp3")),
+                Entry::Search(String::from("lofi")),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_json() {
+        assert!(parse("{\"tracks\": [", Format::Json).is_err());
+        assert!(parse("{\"name\": \"mix\"}", Format::Json).is_err());
+        assert!(parse("", Format::Json).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+use serenity::model::id::{GuildId, UserId};
+use serenity::prelude::*;
+use songbird::tracks::TrackHandle;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{error, info};
+
+use crate::player::is_attachment;
+use crate::settings::data_dir;
+
+/* Playlists container */
+pub struct PlaylistsContainer;
+
+impl TypeMapKey for PlaylistsContainer {
+    type Value = Arc<RwLock<Playlists>>;
+}
+
+// Who a playlist belongs to, personal playlists go with their owner to every guild
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scope {
+    User(UserId),
+    Guild(GuildId),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Playlist {
+    // As it was typed when saving, lookups ignore case
+    pub name: String,
+    pub owner: UserId,
+    pub tracks: Vec<PlaylistTrack>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlaylistTrack {
+    pub url: String,
+    pub title: Option<String>,
+    // In seconds, None for live streams
+    pub duration: Option<u64>,
+}
+
+impl PlaylistTrack {
+    // None for tracks that can't be fetched again, like ones without a source url
+    // or played from an attachment
+    pub fn from_track(track: &TrackHandle) -> Option<Self> {
+        let metadata = track.metadata();
+        Some(PlaylistTrack {
+            url: metadata
+                .source_url
+                .clone()
+                .filter(|url| !is_attachment(url))?,
+            title: metadata.title.clone(),
+            duration: metadata.duration.map(|duration| duration.as_secs()),
+        })
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Store {
+    users: HashMap<UserId, BTreeMap<String, Playlist>>,
+    guilds: HashMap<GuildId, BTreeMap<String, Playlist>>,
+}
+
+pub struct Playlists {
+    path: PathBuf,
+    store: Store,
+}
+
+impl Playlists {
+    pub fn load() -> Self {
+        let path = data_dir().join("playlists.json");
+
+        let store = match std::fs::read_to_string(&path) {
+            Ok(raw) => match serde_json::from_str(&raw) {
+                Ok(store) => store,
+                Err(why) => {
+                    error!("Err parsing {}: {:?}", path.display(), why);
+                    Store::default()
+                }
+            },
+            Err(_) => {
+                info!("No playlists found at {}", path.display());
+                Store::default()
+            }
+        };
+
+        Playlists { path, store }
+    }
+
+    fn scope(&self, scope: Scope) -> Option<&BTreeMap<String, Playlist>> {
+        match scope {
+            Scope::User(user_id) => self.store.users.get(&user_id),
+            Scope::Guild(guild_id) => self.store.guilds.get(&guild_id),
+        }
+    }
+
+    fn scope_mut(&mut self, scope: Scope) -> &mut BTreeMap<String, Playlist> {
+        match scope {
+            Scope::User(user_id) => self.store.users.entry(user_id).or_default(),
+            Scope::Guild(guild_id) => self.store.guilds.entry(guild_id).or_default(),
+        }
+    }
+
+    pub fn get(&self, scope: Scope, name: &str) -> Option<Playlist> {
+        self.scope(scope)?.get(&name.to_lowercase()).cloned()
+    }
+
+    // Sorted by name
+    pub fn list(&self, scope: Scope) -> Vec<Playlist> {
+        self.scope(scope)
+            .map(|playlists| playlists.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    // Adds the playlist or replaces the one with the same name
+    pub async fn insert(&mut self, scope: Scope, playlist: Playlist) -> std::io::Result<()> {
+        self.scope_mut(scope)
+            .insert(playlist.name.to_lowercase(), playlist);
+        self.save().await
+    }
+
+    pub async fn remove(&mut self, scope: Scope, name: &str) -> std::io::Result<Option<Playlist>> {
+        let removed = self.scope_mut(scope).remove(&name.to_lowercase());
+        if removed.is_some() {
+            self.save().await?;
+        }
+        Ok(removed)
+    }
+
+    async fn save(&self) -> std::io::Result<()> {
+        let raw = serde_json::to_string_pretty(&self.store)?;
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+
+        // write to a temporary file first so a crash can't leave half a file behind
+        let tmp = self.path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, raw).await?;
+        tokio::fs::rename(&tmp, &self.path).await
+    }
+}
+
+pub async fn playlists(data: &RwLock<TypeMap>) -> Arc<RwLock<Playlists>> {
+    let data = data.read().await;
+    data.get::<PlaylistsContainer>()
+        .expect("Playlists placed in at initialisation.")
+        .clone()
+}
+
+// A user's own playlist wins over the guild's one with the same name
+pub async fn find_playlist(
+    data: &RwLock<TypeMap>,
+    user_id: UserId,
+    guild_id: GuildId,
+    name: &str,
+) -> Option<(Scope, Playlist)> {
+    let playlists = playlists(data).await;
+    let playlists = playlists.read().await;
+    [Scope::User(user_id), Scope::Guild(guild_id)]
+        .into_iter()
+        .find_map(|scope| playlists.get(scope, name).map(|playlist| (scope, playlist)))
+}
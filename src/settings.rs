@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+use serenity::model::id::{GuildId, RoleId};
+use serenity::prelude::*;
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{error, info};
+
+/* Settings container */
+pub struct SettingsContainer;
+
+impl TypeMapKey for SettingsContainer {
+    type Value = Arc<RwLock<Settings>>;
+}
+
+// Directory for everything the bot keeps on disk, set with DATA_DIR
+pub fn data_dir() -> PathBuf {
+    PathBuf::from(env::var("DATA_DIR").unwrap_or_else(|_| String::from("data")))
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoopMode {
+    #[default]
+    Off,
+    // Repeat the current track
+    Track,
+    // Put every finished track back at the end of the queue
+    Queue,
+}
+
+impl fmt::Display for LoopMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoopMode::Off => write!(f, "Off"),
+            LoopMode::Track => write!(f, "Track"),
+            LoopMode::Queue => write!(f, "Queue"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildSettings {
+    // Volume in percent, 0 - 200
+    pub volume: u8,
+    pub loop_mode: LoopMode,
+    // Percentage of listeners that have to vote before a skip goes through
+    pub skip_ratio: u8,
+    pub dj_role: Option<RoleId>,
+    // Only DJs can change the queue
+    pub dj_only: bool,
+}
+
+impl Default for GuildSettings {
+    fn default() -> Self {
+        GuildSettings {
+            volume: 100,
+            loop_mode: LoopMode::Off,
+            skip_ratio: 50,
+            dj_role: None,
+            dj_only: false,
+        }
+    }
+}
+
+impl GuildSettings {
+    // Volume as songbird expects it, where 1.0 is the original volume
+    pub fn volume(&self) -> f32 {
+        self.volume as f32 / 100.0
+    }
+}
+
+pub struct Settings {
+    path: PathBuf,
+    guilds: HashMap<GuildId, GuildSettings>,
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let path = data_dir().join("settings.json");
+
+        let guilds = match std::fs::read_to_string(&path) {
+            Ok(raw) => match serde_json::from_str(&raw) {
+                Ok(guilds) => guilds,
+                Err(why) => {
+                    error!("Err parsing {}: {:?}", path.display(), why);
+                    HashMap::new()
+                }
+            },
+            Err(_) => {
+                info!("No settings found at {}, using defaults", path.display());
+                HashMap::new()
+            }
+        };
+
+        Settings { path, guilds }
+    }
+
+    pub fn get(&self, guild_id: GuildId) -> GuildSettings {
+        self.guilds.get(&guild_id).cloned().unwrap_or_default()
+    }
+
+    // Changes the settings of a guild and writes them to disk right away
+    pub async fn update<F>(&mut self, guild_id: GuildId, f: F) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut GuildSettings),
+    {
+        f(self.guilds.entry(guild_id).or_default());
+
+        let raw = serde_json::to_string_pretty(&self.guilds)?;
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+
+        // write to a temporary file first so a crash can't leave half a file behind
+        let tmp = self.path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, raw).await?;
+        tokio::fs::rename(&tmp, &self.path).await
+    }
+}
+
+pub async fn guild_settings(data: &RwLock<TypeMap>, guild_id: GuildId) -> GuildSettings {
+    let data = data.read().await;
+    let settings = data
+        .get::<SettingsContainer>()
+        .expect("Settings placed in at initialisation.");
+    let guild_settings = settings.read().await.get(guild_id);
+    guild_settings
+}
+
+pub async fn update_guild_settings<F>(
+    data: &RwLock<TypeMap>,
+    guild_id: GuildId,
+    f: F,
+) -> std::io::Result<()>
+where
+    F: FnOnce(&mut GuildSettings),
+{
+    let settings = {
+        let data = data.read().await;
+        data.get::<SettingsContainer>()
+            .expect("Settings placed in at initialisation.")
+            .clone()
+    };
+    let result = settings.write().await.update(guild_id, f).await;
+    result
+}
@@ -0,0 +1,42 @@
+use serenity::async_trait;
+use std::path::Path;
+
+use crate::sources::{web_url, ResolveError, Source, SourceResolver, TrackDescriptor};
+
+// Files ffmpeg can stream straight from a link
+pub const MEDIA_EXTENSIONS: [&str; 8] = ["mp3", "ogg", "flac", "wav", "mp4", "m4a", "webm", "opus"];
+
+pub fn has_media_extension(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| MEDIA_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+// Links to audio or video files, like https://example.com/mix.mp3
+pub struct DirectMedia;
+
+#[async_trait]
+impl SourceResolver for DirectMedia {
+    fn name(&self) -> &'static str {
+        "direct media link"
+    }
+
+    fn claims(&self, input: &str) -> bool {
+        web_url(input)
+            .map(|url| has_media_extension(url.path()))
+            .unwrap_or(false)
+    }
+
+    async fn resolve(&self, input: &str) -> Result<Vec<TrackDescriptor>, ResolveError> {
+        let mut track = TrackDescriptor::new(Source::Ffmpeg(input.to_string()));
+        track.title = web_url(input).and_then(|url| {
+            url.path_segments()
+                .and_then(|mut segments| segments.next_back())
+                .filter(|name| !name.is_empty())
+                .map(|name| name.to_string())
+        });
+        Ok(vec![track])
+    }
+}
@@ -0,0 +1,112 @@
+use serenity::async_trait;
+use songbird::input::error::{Error as InputError, Result as InputResult};
+use songbird::input::restartable::Restart;
+use songbird::input::{ffmpeg_optioned, Codec, Container, Input, Metadata};
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::sources::{retry, ytdlp, ResolveError};
+
+// A yt-dlp track that only knows what its playlist said about it. Nothing is
+// spawned until songbird wants its audio, right before it plays.
+pub struct LazyYtdl {
+    pub url: String,
+    // The uuid of the track that plays it
+    pub uuid: Uuid,
+    pub metadata: Metadata,
+}
+
+// Why queued tracks couldn't be fetched when their turn came, by track uuid,
+// until their end event tells the channel
+static FAILURES: Mutex<BTreeMap<Uuid, String>> = Mutex::new(BTreeMap::new());
+
+pub fn take_failure(uuid: Uuid) -> Option<String> {
+    FAILURES.lock().unwrap().remove(&uuid)
+}
+
+impl LazyYtdl {
+    async fn stream(&self) -> Result<String, ResolveError> {
+        let url = &self.url;
+        let media = retry(url, || async { Ok(ytdlp::media(url).await?) }).await?;
+        media.stream_url().ok_or(ResolveError::NothingFound)
+    }
+}
+
+#[async_trait]
+impl Restart for LazyYtdl {
+    async fn call_restart(&mut self, time: Option<Duration>) -> InputResult<Input> {
+        info!("Fetching queued track --> {}", self.url);
+        let stream = match self.stream().await {
+            Ok(stream) => stream,
+            Err(why) => {
+                error!("Err fetching queued track {}: {:?}", self.url, why);
+                let reason = why.to_string();
+                FAILURES.lock().unwrap().insert(self.uuid, reason.clone());
+                return Err(InputError::Io(io::Error::other(reason)));
+            }
+        };
+
+        // Songbird starts lazy tracks at 0, so there's always a position to pass
+        let start = format!("{:.3}", time.unwrap_or_default().as_secs_f64());
+        let mut input = ffmpeg_optioned(
+            &stream,
+            &["-ss", &start],
+            &[
+                "-f",
+                "s16le",
+                "-ac",
+                "2",
+                "-ar",
+                "48000",
+                "-acodec",
+                "pcm_f32le",
+                "-",
+            ],
+        )
+        .await?;
+        // Two channels were asked for above, whatever the source has
+        input.stereo = true;
+        Ok(input)
+    }
+
+    // The playlist's metadata stands in for what ffprobe would say. Restarts always
+    // give two channels, anything else has songbird play them at the wrong speed.
+    async fn lazy_init(&mut self) -> InputResult<(Option<Metadata>, Codec, Container)> {
+        let metadata = Metadata {
+            channels: Some(2),
+            ..self.metadata.clone()
+        };
+        Ok((Some(metadata), Codec::FloatPcm, Container::Raw))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::{Source, TrackDescriptor};
+
+    #[tokio::test]
+    async fn lazy_tracks_are_stereo() {
+        let mut lazy = LazyYtdl {
+            url: String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            uuid: Uuid::new_v4(),
+            metadata: Metadata::default(),
+        };
+        let (metadata, _, _) = lazy.lazy_init().await.unwrap();
+        assert_eq!(metadata.unwrap().channels, Some(2));
+    }
+
+    #[tokio::test]
+    async fn playlist_entries_play_in_stereo() {
+        let mut track = TrackDescriptor::new(Source::Ytdl(String::from(
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+        )));
+        track.title = Some(String::from("Never Gonna Give You Up"));
+        let playable = track.input().await.unwrap();
+        assert!(playable.input.stereo);
+    }
+}
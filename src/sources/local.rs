@@ -0,0 +1,27 @@
+use serenity::async_trait;
+use std::path::Path;
+
+use crate::library;
+use crate::sources::{ResolveError, Source, SourceResolver, TrackDescriptor};
+
+// Paths to files in the music library
+pub struct LocalFile;
+
+#[async_trait]
+impl SourceResolver for LocalFile {
+    fn name(&self) -> &'static str {
+        "local file"
+    }
+
+    fn claims(&self, input: &str) -> bool {
+        library::is_local(input)
+    }
+
+    async fn resolve(&self, input: &str) -> Result<Vec<TrackDescriptor>, ResolveError> {
+        let mut track = TrackDescriptor::new(Source::Ffmpeg(input.to_string()));
+        track.title = Path::new(input)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string());
+        Ok(vec![track])
+    }
+}
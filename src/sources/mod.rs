@@ -0,0 +1,372 @@
+pub mod http;
+pub mod lazy;
+pub mod local;
+pub mod search;
+pub mod youtube;
+pub mod ytdl;
+pub mod ytdlp;
+
+use serenity::async_trait;
+use songbird::input::error::Error as InputError;
+use songbird::input::{Input, Metadata, Restartable};
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{info, warn};
+use url::Url;
+use uuid::Uuid;
+
+use crate::library;
+use crate::sources::lazy::LazyYtdl;
+use crate::sources::ytdlp::YtdlError;
+
+// Tries after the first one, and how long to wait before the first retry
+const RETRIES: u32 = 3;
+const RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+// How a track's audio gets fetched
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    // Anything yt-dlp can extract
+    Ytdl(String),
+    // The first YouTube search result
+    Search(String),
+    // Local files and direct links ffmpeg can read by itself
+    Ffmpeg(String),
+}
+
+// A track a resolver found, with whatever it already knows about it
+#[derive(Clone, Debug)]
+pub struct TrackDescriptor {
+    pub source: Source,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub duration: Option<Duration>,
+    pub thumbnail: Option<String>,
+    // Where playback starts, from timestamps in links
+    pub start: Option<Duration>,
+    // Private or deleted, the playlist listed it anyway
+    pub unavailable: bool,
+}
+
+impl TrackDescriptor {
+    pub fn new(source: Source) -> Self {
+        TrackDescriptor {
+            source,
+            title: None,
+            artist: None,
+            duration: None,
+            thumbnail: None,
+            start: None,
+            unavailable: false,
+        }
+    }
+
+    // For source urls of tracks that were queued before, files from the music library
+    // are the only ones that don't go through yt-dlp
+    pub fn from_url(url: &str) -> Self {
+        match library::is_local(url) {
+            true => TrackDescriptor::new(Source::Ffmpeg(url.to_string())),
+            false => TrackDescriptor::new(Source::Ytdl(url.to_string())),
+        }
+    }
+
+    // What's known about the track before fetching it
+    pub fn metadata(&self) -> Metadata {
+        Metadata {
+            title: self.title.clone(),
+            artist: self.artist.clone(),
+            duration: self.duration,
+            thumbnail: self.thumbnail.clone(),
+            source_url: match &self.source {
+                Source::Search(_) => None,
+                Source::Ytdl(url) | Source::Ffmpeg(url) => Some(url.clone()),
+            },
+            ..Default::default()
+        }
+    }
+
+    // What to call the track when it can't be fetched
+    pub fn label(&self) -> String {
+        let location = match &self.source {
+            Source::Ytdl(url) | Source::Ffmpeg(url) => url.clone(),
+            Source::Search(query) => format!("search: {query}"),
+        };
+        match &self.title {
+            Some(title) => format!("{title} ({location})"),
+            None => location,
+        }
+    }
+
+    // Like input, but gives flaky errors a few more tries
+    pub async fn input_retrying(&self) -> Result<Playable, ResolveError> {
+        retry(&self.label(), || self.input()).await
+    }
+
+    // Starts fetching the audio. What the source reports about itself wins, the
+    // descriptor fills in the gaps.
+    pub async fn input(&self) -> Result<Playable, ResolveError> {
+        if self.unavailable {
+            return Err(ResolveError::Ytdl(YtdlError::Unavailable));
+        }
+
+        let uuid = Uuid::new_v4();
+        let mut input: Input = match &self.source {
+            // Playlist entries already say what they are, so they wait to be played
+            Source::Ytdl(url) if self.title.is_some() => {
+                let lazy = LazyYtdl {
+                    url: url.clone(),
+                    uuid,
+                    metadata: self.metadata(),
+                };
+                Restartable::new(lazy, true).await?.into()
+            }
+            Source::Ytdl(url) => ytdl_input(url, uuid).await?,
+            Source::Search(query) => ytdl_input(&format!("ytsearch1:{query}"), uuid).await?,
+            Source::Ffmpeg(path) => {
+                let mut input: Input = Restartable::ffmpeg(path.clone(), true).await?.into();
+                // ffprobe doesn't know where the file came from, or its name
+                input.metadata.source_url = Some(path.clone());
+                input.metadata.title = self.title.clone().or(input.metadata.title.take());
+                input
+            }
+        };
+
+        let metadata = &mut input.metadata;
+        metadata.title = metadata.title.take().or_else(|| self.title.clone());
+        metadata.artist = metadata.artist.take().or_else(|| self.artist.clone());
+        metadata.duration = metadata.duration.or(self.duration);
+        metadata.thumbnail = metadata.thumbnail.take().or_else(|| self.thumbnail.clone());
+        Ok(Playable { input, uuid })
+    }
+}
+
+// Audio ready to be queued. Lazy tracks report why they couldn't be fetched
+// under the uuid, so the track has to be queued with it.
+pub struct Playable {
+    pub input: Input,
+    pub uuid: Uuid,
+}
+
+// Looks the track up now for what it is, but leaves picking the stream for when
+// it plays, stream links expire long before a busy queue gets to them. The page
+// url is what gets remembered as the source.
+async fn ytdl_input(target: &str, uuid: Uuid) -> Result<Input, ResolveError> {
+    let media = ytdlp::media(target).await?;
+    if media.stream_url().is_none() {
+        return Err(ResolveError::NothingFound);
+    }
+
+    let url = media
+        .webpage_url
+        .or_else(|| web_url(target).map(|_| target.to_string()))
+        .ok_or(ResolveError::NothingFound)?;
+    let metadata = Metadata {
+        source_url: Some(url.clone()),
+        title: media.title,
+        artist: media.channel.or(media.uploader),
+        thumbnail: media.thumbnail,
+        date: media.upload_date,
+        duration: match media.is_live {
+            Some(true) => None,
+            _ => media.duration.map(Duration::from_secs_f64),
+        },
+        ..Default::default()
+    };
+    Ok(Restartable::new(
+        LazyYtdl {
+            url,
+            uuid,
+            metadata,
+        },
+        true,
+    )
+    .await?
+    .into())
+}
+
+#[derive(Debug)]
+pub enum ResolveError {
+    // The input made sense but there's nothing to play behind it
+    NothingFound,
+    Ytdl(YtdlError),
+    // ffmpeg couldn't read the audio
+    Input(InputError),
+}
+
+impl ResolveError {
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ResolveError::NothingFound => false,
+            ResolveError::Ytdl(why) => why.is_transient(),
+            // Only ffmpeg failing to start, a file it can't read stays unreadable
+            ResolveError::Input(InputError::Io(_)) => true,
+            ResolveError::Input(_) => false,
+        }
+    }
+}
+
+impl From<YtdlError> for ResolveError {
+    fn from(why: YtdlError) -> Self {
+        ResolveError::Ytdl(why)
+    }
+}
+
+impl From<InputError> for ResolveError {
+    fn from(why: InputError) -> Self {
+        ResolveError::Input(why)
+    }
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveError::NothingFound => write!(f, "Nothing playable was found."),
+            ResolveError::Ytdl(why) => write!(f, "{why}"),
+            ResolveError::Input(_) => write!(f, "The audio couldn't be read."),
+        }
+    }
+}
+
+// Tries again after transient errors, waiting twice as long each time
+pub async fn retry<T, F, Fut>(what: &str, mut attempt: F) -> Result<T, ResolveError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ResolveError>>,
+{
+    let mut delay = RETRY_BACKOFF;
+    for _ in 0..RETRIES {
+        match attempt().await {
+            Err(why) if why.is_transient() => {
+                warn!("Retrying {} in {:?} after: {:?}", what, delay, why);
+                sleep(delay).await;
+                delay *= 2;
+            }
+            result => return result,
+        }
+    }
+    attempt().await
+}
+
+// Turns what someone typed after play into tracks
+#[async_trait]
+pub trait SourceResolver: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // Whether this resolver takes care of the input
+    fn claims(&self, input: &str) -> bool;
+
+    async fn resolve(&self, input: &str) -> Result<Vec<TrackDescriptor>, ResolveError>;
+}
+
+// Asked in order, the first one to claim an input gets it. Search claims everything,
+// so it has to stay last.
+static RESOLVERS: &[&dyn SourceResolver] = &[
+    &local::LocalFile,
+    &youtube::YoutubePlaylist,
+    &youtube::YoutubeVideo,
+    &http::DirectMedia,
+    &ytdl::GenericYtdl,
+    &search::Search,
+];
+
+pub async fn resolve(input: &str) -> Result<Vec<TrackDescriptor>, ResolveError> {
+    let input = input.trim();
+    let resolver = RESOLVERS
+        .iter()
+        .find(|resolver| resolver.claims(input))
+        .expect("Search claims every input.");
+
+    info!("Resolving '{}' with --> {}", input, resolver.name());
+    let tracks = resolver.resolve(input).await?;
+    match tracks.is_empty() {
+        true => Err(ResolveError::NothingFound),
+        false => Ok(tracks),
+    }
+}
+
+// http and https links, everything else is a search or a path
+pub fn web_url(input: &str) -> Option<Url> {
+    Url::parse(input)
+        .ok()
+        .filter(|url| url.scheme() == "http" || url.scheme() == "https")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::Instant;
+
+    // Runs retry with attempts that fail with the given errors in turn, and says
+    // how long after the start each attempt was made
+    async fn attempts(mut errors: Vec<ResolveError>) -> (Result<(), ResolveError>, Vec<Duration>) {
+        errors.reverse();
+        let start = Instant::now();
+        let mut times = Vec::new();
+        let result = retry("test", || {
+            times.push(start.elapsed());
+            let result = match errors.pop() {
+                Some(why) => Err(why),
+                None => Ok(()),
+            };
+            async move { result }
+        })
+        .await;
+        (result, times)
+    }
+
+    fn secs(secs: &[u64]) -> Vec<Duration> {
+        secs.iter().copied().map(Duration::from_secs).collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retry_backs_off_then_gives_up() {
+        let errors = (0..10)
+            .map(|_| ResolveError::Ytdl(YtdlError::Timeout))
+            .collect();
+        let (result, times) = attempts(errors).await;
+        assert!(matches!(
+            result,
+            Err(ResolveError::Ytdl(YtdlError::Timeout))
+        ));
+        assert_eq!(times.len(), RETRIES as usize + 1);
+        assert_eq!(times, secs(&[0, 1, 3, 7]));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retry_stops_at_lasting_errors() {
+        let errors = vec![
+            ResolveError::Ytdl(YtdlError::Timeout),
+            ResolveError::Ytdl(YtdlError::Unavailable),
+            ResolveError::Ytdl(YtdlError::Timeout),
+        ];
+        let (result, times) = attempts(errors).await;
+        assert!(matches!(
+            result,
+            Err(ResolveError::Ytdl(YtdlError::Unavailable))
+        ));
+        assert_eq!(times, secs(&[0, 1]));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retry_returns_the_first_success() {
+        let (result, times) = attempts(vec![ResolveError::Ytdl(YtdlError::Timeout)]).await;
+        assert!(result.is_ok());
+        assert_eq!(times, secs(&[0, 1]));
+
+        let (result, times) = attempts(Vec::new()).await;
+        assert!(result.is_ok());
+        assert_eq!(times, secs(&[0]));
+    }
+
+    #[test]
+    fn only_ffmpeg_failing_to_start_is_retried() {
+        assert!(
+            ResolveError::Input(InputError::Io(std::io::Error::other("spawn failed")))
+                .is_transient()
+        );
+        assert!(!ResolveError::Input(InputError::Streams).is_transient());
+        assert!(!ResolveError::NothingFound.is_transient());
+    }
+}
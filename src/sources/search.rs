@@ -0,0 +1,35 @@
+use serenity::async_trait;
+
+use crate::sources::ytdlp::flat_playlist;
+use crate::sources::{ResolveError, Source, SourceResolver, TrackDescriptor};
+
+// Anything that isn't a link or a file is a YouTube search
+pub struct Search;
+
+#[async_trait]
+impl SourceResolver for Search {
+    fn name(&self) -> &'static str {
+        "YouTube search"
+    }
+
+    fn claims(&self, _: &str) -> bool {
+        true
+    }
+
+    async fn resolve(&self, input: &str) -> Result<Vec<TrackDescriptor>, ResolveError> {
+        Ok(vec![TrackDescriptor::new(Source::Search(input.to_string()))])
+    }
+}
+
+// The top results of a YouTube search, for people to pick from
+pub async fn search(query: &str, count: usize) -> Result<Vec<TrackDescriptor>, ResolveError> {
+    let results = flat_playlist(&format!("ytsearch{count}:{query}"))
+        .await?
+        .into_iter()
+        .filter(|track| !track.unavailable)
+        .collect::<Vec<_>>();
+    match results.is_empty() {
+        true => Err(ResolveError::NothingFound),
+        false => Ok(results),
+    }
+}
@@ -0,0 +1,206 @@
+use serenity::async_trait;
+use std::time::Duration;
+
+use crate::commands::utils::parse_time;
+use crate::sources::ytdlp::flat_playlist;
+use crate::sources::{web_url, ResolveError, Source, SourceResolver, TrackDescriptor};
+
+const HOSTS: [&str; 5] = [
+    "youtube.com",
+    "www.youtube.com",
+    "m.youtube.com",
+    "music.youtube.com",
+    "youtu.be",
+];
+
+// Where a YouTube link points, whichever of its many shapes it came in
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum YoutubeLink {
+    Video { id: String, start: Option<Duration> },
+    Playlist { id: String },
+}
+
+impl YoutubeLink {
+    // The one url yt-dlp gets for it, whatever the link looked like
+    pub fn url(&self) -> String {
+        match self {
+            YoutubeLink::Video { id, .. } => format!("https://www.youtube.com/watch?v={id}"),
+            YoutubeLink::Playlist { id } => format!("https://www.youtube.com/playlist?list={id}"),
+        }
+    }
+}
+
+fn is_video_id(id: &str) -> bool {
+    id.len() == 11
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn is_playlist_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// Mixes and radios are made up for whoever opens them and never end
+fn is_mix(id: &str) -> bool {
+    id.starts_with("RD")
+}
+
+// youtu.be/<id>, /watch?v=<id>, /shorts/<id>, /live/<id>, /embed/<id> and
+// /playlist?list=<id> on any of the YouTube hosts. A video that happens to be in
+// a playlist or a mix is still just the video.
+pub fn normalize(input: &str) -> Option<YoutubeLink> {
+    let url = web_url(input)?;
+    let host = url.host_str()?;
+    if !HOSTS.contains(&host) {
+        return None;
+    }
+
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.to_string())
+    };
+    let start = param("t")
+        .or_else(|| param("start"))
+        .and_then(|raw| parse_time(&raw))
+        .map(Duration::from_secs)
+        .filter(|start| !start.is_zero());
+
+    let segments = url
+        .path_segments()
+        .map(|segments| segments.filter(|s| !s.is_empty()).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let id = match (host, segments.as_slice()) {
+        ("youtu.be", [id]) => Some(id.to_string()),
+        (_, ["watch"]) => param("v"),
+        (_, ["shorts" | "live" | "embed" | "v", id]) => Some(id.to_string()),
+        _ => None,
+    };
+
+    match id {
+        Some(id) if is_video_id(&id) => Some(YoutubeLink::Video { id, start }),
+        Some(_) => None,
+        // watch?list= without a video is the playlist itself
+        None if matches!(segments.as_slice(), ["playlist"] | ["watch"]) => param("list")
+            .filter(|id| is_playlist_id(id) && !is_mix(id))
+            .map(|id| YoutubeLink::Playlist { id }),
+        None => None,
+    }
+}
+
+pub struct YoutubeVideo;
+
+#[async_trait]
+impl SourceResolver for YoutubeVideo {
+    fn name(&self) -> &'static str {
+        "YouTube video"
+    }
+
+    fn claims(&self, input: &str) -> bool {
+        matches!(normalize(input), Some(YoutubeLink::Video { .. }))
+    }
+
+    async fn resolve(&self, input: &str) -> Result<Vec<TrackDescriptor>, ResolveError> {
+        let link = normalize(input).ok_or(ResolveError::NothingFound)?;
+        let mut track = TrackDescriptor::new(Source::Ytdl(link.url()));
+        if let YoutubeLink::Video { start, .. } = link {
+            track.start = start;
+        }
+        Ok(vec![track])
+    }
+}
+
+pub struct YoutubePlaylist;
+
+#[async_trait]
+impl SourceResolver for YoutubePlaylist {
+    fn name(&self) -> &'static str {
+        "YouTube playlist"
+    }
+
+    fn claims(&self, input: &str) -> bool {
+        matches!(normalize(input), Some(YoutubeLink::Playlist { .. }))
+    }
+
+    // Only lists the videos, fetching each of them is up to whoever queues them
+    async fn resolve(&self, input: &str) -> Result<Vec<TrackDescriptor>, ResolveError> {
+        let url = normalize(input).ok_or(ResolveError::NothingFound)?.url();
+        Ok(flat_playlist(&url).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video(id: &str, start: Option<u64>) -> Option<YoutubeLink> {
+        Some(YoutubeLink::Video {
+            id: id.to_string(),
+            start: start.map(Duration::from_secs),
+        })
+    }
+
+    #[test]
+    fn short_links() {
+        assert_eq!(
+            normalize("https://youtu.be/dQw4w9WgXcQ"),
+            video("dQw4w9WgXcQ", None)
+        );
+        assert_eq!(
+            normalize("https://youtu.be/dQw4w9WgXcQ?t=43"),
+            video("dQw4w9WgXcQ", Some(43))
+        );
+    }
+
+    #[test]
+    fn shorts_and_live() {
+        assert_eq!(
+            normalize("https://www.youtube.com/shorts/dQw4w9WgXcQ"),
+            video("dQw4w9WgXcQ", None)
+        );
+        assert_eq!(
+            normalize("https://m.youtube.com/live/dQw4w9WgXcQ?feature=share"),
+            video("dQw4w9WgXcQ", None)
+        );
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(
+            normalize("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=1m30s"),
+            video("dQw4w9WgXcQ", Some(90))
+        );
+        assert_eq!(
+            normalize("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=0"),
+            video("dQw4w9WgXcQ", None)
+        );
+        // Too large to be a real position, so it's ignored rather than overflowing
+        assert_eq!(
+            normalize("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=9999999999999999h"),
+            video("dQw4w9WgXcQ", None)
+        );
+    }
+
+    #[test]
+    fn mixes() {
+        assert_eq!(
+            normalize("https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=RDdQw4w9WgXcQ"),
+            video("dQw4w9WgXcQ", None)
+        );
+        assert_eq!(
+            normalize("https://www.youtube.com/watch?list=RDdQw4w9WgXcQ"),
+            None
+        );
+        assert_eq!(
+            normalize("https://www.youtube.com/playlist?list=PLFgquLnL59alCl_2TQvOiD5Vgm1hCaGSI"),
+            Some(YoutubeLink::Playlist {
+                id: String::from("PLFgquLnL59alCl_2TQvOiD5Vgm1hCaGSI")
+            })
+        );
+    }
+}
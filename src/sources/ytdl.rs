@@ -0,0 +1,21 @@
+use serenity::async_trait;
+
+use crate::sources::{web_url, ResolveError, Source, SourceResolver, TrackDescriptor};
+
+// Every other link, yt-dlp knows SoundCloud, Bandcamp, Twitch and a lot more
+pub struct GenericYtdl;
+
+#[async_trait]
+impl SourceResolver for GenericYtdl {
+    fn name(&self) -> &'static str {
+        "yt-dlp"
+    }
+
+    fn claims(&self, input: &str) -> bool {
+        web_url(input).is_some()
+    }
+
+    async fn resolve(&self, input: &str) -> Result<Vec<TrackDescriptor>, ResolveError> {
+        Ok(vec![TrackDescriptor::new(Source::Ytdl(input.to_string()))])
+    }
+}
@@ -0,0 +1,427 @@
+use serde::Deserialize;
+use std::env;
+use std::fmt;
+use std::io;
+use std::process::Output;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::time::timeout;
+use tracing::{info, warn};
+
+use crate::sources::{Source, TrackDescriptor};
+
+// Every yt-dlp call goes through here. YTDL_PATH, YTDL_COOKIES, YTDL_PROXY,
+// YTDL_FORMAT, YTDL_ARGS and YTDL_TIMEOUT change how it's run.
+
+fn binary() -> String {
+    env::var("YTDL_PATH").unwrap_or_else(|_| String::from("yt-dlp"))
+}
+
+fn format() -> String {
+    env::var("YTDL_FORMAT").unwrap_or_else(|_| String::from("bestaudio/best"))
+}
+
+// How long a single video gets, listing a playlist gets a few times that
+fn call_timeout() -> Duration {
+    let secs = env::var("YTDL_TIMEOUT")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or(30);
+    Duration::from_secs(secs)
+}
+
+// Options every call shares, whatever it's for
+fn common_args() -> Vec<String> {
+    let mut args = vec![String::from("--no-warnings")];
+    if let Ok(cookies) = env::var("YTDL_COOKIES") {
+        if !cookies.is_empty() {
+            args.push(String::from("--cookies"));
+            args.push(cookies);
+        }
+    }
+    if let Ok(proxy) = env::var("YTDL_PROXY") {
+        if !proxy.is_empty() {
+            args.push(String::from("--proxy"));
+            args.push(proxy);
+        }
+    }
+    if let Ok(extra) = env::var("YTDL_ARGS") {
+        args.extend(extra.split_whitespace().map(String::from));
+    }
+    args
+}
+
+#[derive(Debug)]
+pub enum YtdlError {
+    NotInstalled,
+    Timeout,
+    Unavailable,
+    AgeRestricted,
+    GeoBlocked,
+    // Anything else yt-dlp complained about, with its error message
+    Failed(String),
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for YtdlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            YtdlError::NotInstalled => write!(f, "yt-dlp isn't installed."),
+            YtdlError::Timeout => write!(f, "yt-dlp took too long to answer."),
+            YtdlError::Unavailable => write!(f, "The video is unavailable."),
+            YtdlError::AgeRestricted => write!(f, "The video is age restricted."),
+            YtdlError::GeoBlocked => write!(f, "The video isn't available in this country."),
+            YtdlError::Failed(reason) => write!(f, "yt-dlp failed: {reason}"),
+            YtdlError::Io(why) => write!(f, "yt-dlp couldn't be started: {why}"),
+            YtdlError::Json(why) => write!(f, "yt-dlp's answer couldn't be read: {why}"),
+        }
+    }
+}
+
+// What yt-dlp says when the network let it down rather than the video
+const NETWORK_ERRORS: [&str; 8] = [
+    "timed out",
+    "urlopen error",
+    "connection reset",
+    "connection refused",
+    "connection aborted",
+    "remote end closed connection",
+    "temporary failure in name resolution",
+    "incompleteread",
+];
+
+impl YtdlError {
+    // Worth trying again, the video itself is fine as far as we know
+    pub fn is_transient(&self) -> bool {
+        match self {
+            YtdlError::Timeout | YtdlError::Io(_) => true,
+            YtdlError::Failed(reason) => {
+                let reason = reason.to_lowercase();
+                // 5xx means YouTube itself had a bad moment
+                reason.contains("http error 5")
+                    || NETWORK_ERRORS.iter().any(|error| reason.contains(error))
+            }
+            _ => false,
+        }
+    }
+}
+
+// yt-dlp's last ERROR: line tells what went wrong. It starts with the video id
+// and can quote its title, so only yt-dlp's own phrases are matched.
+fn classify(stderr: &str) -> YtdlError {
+    let reason = stderr
+        .lines()
+        .rev()
+        .find(|line| line.starts_with("ERROR:"))
+        .or_else(|| stderr.lines().last())
+        .unwrap_or_default()
+        .trim_start_matches("ERROR:")
+        .trim()
+        .to_string();
+    let lowercase = reason.to_lowercase();
+
+    if lowercase.contains("confirm your age") || lowercase.contains("age-restricted") {
+        YtdlError::AgeRestricted
+    } else if lowercase.contains("not made this video available in your country")
+        || lowercase.contains("not available from your location")
+        || lowercase.contains("geo restrict")
+        || lowercase.contains("geo-restrict")
+    {
+        YtdlError::GeoBlocked
+    } else if lowercase.contains("unavailable")
+        || lowercase.contains("not available")
+        || lowercase.contains("private video")
+        || lowercase.contains("has been removed")
+    {
+        YtdlError::Unavailable
+    } else {
+        YtdlError::Failed(reason)
+    }
+}
+
+// Runs yt-dlp, killing it if it doesn't finish in time
+async fn run(args: &[&str], limit: Duration) -> Result<Output, YtdlError> {
+    let child = Command::new(binary())
+        .args(common_args())
+        .args(args)
+        .kill_on_drop(true)
+        .output();
+
+    match timeout(limit, child).await {
+        Ok(Ok(output)) => Ok(output),
+        Ok(Err(why)) if why.kind() == io::ErrorKind::NotFound => Err(YtdlError::NotInstalled),
+        Ok(Err(why)) => Err(YtdlError::Io(why)),
+        Err(_) => Err(YtdlError::Timeout),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Thumbnail {
+    pub url: String,
+}
+
+// One line of `yt-dlp -j --flat-playlist`, only what's known without fetching
+// the video itself
+#[derive(Debug, Deserialize)]
+pub struct FlatEntry {
+    pub id: String,
+    pub url: Option<String>,
+    pub ie_key: Option<String>,
+    pub title: Option<String>,
+    pub duration: Option<f64>,
+    pub uploader: Option<String>,
+    pub channel: Option<String>,
+    pub thumbnail: Option<String>,
+    #[serde(default)]
+    pub thumbnails: Vec<Thumbnail>,
+    // public, unlisted, private, needs_auth, subscriber_only or premium_only
+    pub availability: Option<String>,
+}
+
+impl FlatEntry {
+    // Flat YouTube entries sometimes only come with an id
+    pub fn url(&self) -> Option<String> {
+        match (&self.url, self.ie_key.as_deref()) {
+            (Some(url), _) if url.starts_with("http") => Some(url.clone()),
+            (_, Some("Youtube")) => Some(format!("https://www.youtube.com/watch?v={}", self.id)),
+            _ => None,
+        }
+    }
+
+    // Private and deleted videos stay in playlists, but can't be played
+    pub fn is_playable(&self) -> bool {
+        let available = !matches!(
+            self.availability.as_deref(),
+            Some("private" | "needs_auth" | "subscriber_only" | "premium_only")
+        );
+        let removed = matches!(
+            self.title.as_deref(),
+            Some("[Private video]" | "[Deleted video]")
+        );
+        available && !removed
+    }
+
+    pub fn descriptor(&self) -> Option<TrackDescriptor> {
+        let mut track = TrackDescriptor::new(Source::Ytdl(self.url()?));
+        track.title = self.title.clone();
+        track.artist = self.channel.clone().or_else(|| self.uploader.clone());
+        track.duration = self
+            .duration
+            .filter(|duration| *duration > 0.0)
+            .map(Duration::from_secs_f64);
+        // The largest thumbnail comes last
+        track.thumbnail = self.thumbnail.clone().or_else(|| {
+            self.thumbnails
+                .last()
+                .map(|thumbnail| thumbnail.url.clone())
+        });
+        Some(track)
+    }
+}
+
+// Lists a playlist or search without fetching the videos in it, logging
+// whatever is wrong with its entries
+pub async fn flat_playlist(target: &str) -> Result<Vec<TrackDescriptor>, YtdlError> {
+    let output = run(&["-j", "--flat-playlist", target], call_timeout() * 4).await?;
+
+    let mut tracks = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let entry = match serde_json::from_str::<FlatEntry>(line) {
+            Ok(entry) => entry,
+            Err(why) => {
+                warn!("Skipping yt-dlp entry that couldn't be read: {}", why);
+                continue;
+            }
+        };
+
+        match entry.descriptor() {
+            // Kept so they show up as failed, and so ranges count like the playlist
+            Some(mut track) if !entry.is_playable() => {
+                warn!("Unavailable entry {} ({:?})", entry.id, entry.availability);
+                track.unavailable = true;
+                tracks.push(track);
+            }
+            Some(track) => tracks.push(track),
+            None => warn!("Skipping entry {} without a url", entry.id),
+        }
+    }
+
+    if tracks.is_empty() && !output.status.success() {
+        return Err(classify(&String::from_utf8_lossy(&output.stderr)));
+    }
+    Ok(tracks)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Format {
+    pub url: String,
+}
+
+// What `yt-dlp -j` says about a single video, with the stream of the picked format
+#[derive(Debug, Deserialize)]
+pub struct Media {
+    pub url: Option<String>,
+    #[serde(default)]
+    pub requested_formats: Vec<Format>,
+    pub webpage_url: Option<String>,
+    pub title: Option<String>,
+    pub uploader: Option<String>,
+    pub channel: Option<String>,
+    pub duration: Option<f64>,
+    pub thumbnail: Option<String>,
+    // YYYYMMDD
+    pub upload_date: Option<String>,
+    pub is_live: Option<bool>,
+}
+
+impl Media {
+    // Formats that come in separate video and audio streams list the audio last
+    pub fn stream_url(&self) -> Option<String> {
+        self.url.clone().or_else(|| {
+            self.requested_formats
+                .last()
+                .map(|format| format.url.clone())
+        })
+    }
+}
+
+// Looks up a single video, or the first result of a ytsearch1: query
+pub async fn media(target: &str) -> Result<Media, YtdlError> {
+    info!("Fetching media info for --> {}", target);
+    let format = format();
+    let output = run(
+        &["-j", "--no-playlist", "-f", &format, target],
+        call_timeout(),
+    )
+    .await?;
+
+    if !output.status.success() {
+        return Err(classify(&String::from_utf8_lossy(&output.stderr)));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .ok_or(YtdlError::Unavailable)?;
+    serde_json::from_str(line).map_err(YtdlError::Json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(error: &YtdlError) -> &'static str {
+        match error {
+            YtdlError::NotInstalled => "not installed",
+            YtdlError::Timeout => "timeout",
+            YtdlError::Unavailable => "unavailable",
+            YtdlError::AgeRestricted => "age restricted",
+            YtdlError::GeoBlocked => "geo blocked",
+            YtdlError::Failed(_) => "failed",
+            YtdlError::Io(_) => "io",
+            YtdlError::Json(_) => "json",
+        }
+    }
+
+    #[test]
+    fn classifies_errors() {
+        let cases = [
+            (
+                "ERROR: [youtube] dQw4w9WgXcQ: Sign in to confirm your age. This video may be inappropriate for some users. Use --cookies-from-browser or --cookies for the authentication.",
+                "age restricted",
+            ),
+            (
+                "ERROR: [youtube] dQw4w9WgXcQ: The uploader has not made this video available in your country",
+                "geo blocked",
+            ),
+            (
+                "ERROR: [generic] news-clip: This video is not available from your location due to geo restriction. You might want to use a VPN or a proxy server (with --proxy) to workaround.",
+                "geo blocked",
+            ),
+            (
+                "ERROR: [generic] country-roads: Unable to download webpage: HTTP Error 404: Not Found (caused by <HTTPError 404: Not Found>)",
+                "failed",
+            ),
+            (
+                "ERROR: [generic] geography-lesson: Unable to download webpage: HTTP Error 404: Not Found (caused by <HTTPError 404: Not Found>)",
+                "failed",
+            ),
+            (
+                "ERROR: [youtube] dQw4w9WgXcQ: Private video. Sign in if you've been granted access to this video",
+                "unavailable",
+            ),
+            (
+                "ERROR: [youtube] dQw4w9WgXcQ: Video unavailable. This video has been removed by the uploader",
+                "unavailable",
+            ),
+            (
+                "ERROR: [youtube] dQw4w9WgXcQ: Unable to extract uploader id; please report this issue on  https://github.com/yt-dlp/yt-dlp/issues?q= , filling out the appropriate issue template.",
+                "failed",
+            ),
+        ];
+
+        for (stderr, expected) in cases {
+            let stderr =
+                format!("WARNING: [youtube] Falling back to generic n function search\n{stderr}\n");
+            assert_eq!(kind(&classify(&stderr)), expected, "{stderr}");
+        }
+    }
+
+    #[test]
+    fn retries_network_errors_only() {
+        let transient = [
+            "[youtube] dQw4w9WgXcQ: Unable to download webpage: <urlopen error [Errno -3] Temporary failure in name resolution> (caused by TransportError('<urlopen error [Errno -3] Temporary failure in name resolution>'))",
+            "[youtube] dQw4w9WgXcQ: Unable to download API page: HTTP Error 503: Service Unavailable (caused by <HTTPError 503: Service Unavailable>)",
+            "[youtube] dQw4w9WgXcQ: Unable to download webpage: The read operation timed out (caused by TransportError('The read operation timed out'))",
+            "[youtube] dQw4w9WgXcQ: Unable to download webpage: ('Connection aborted.', RemoteDisconnected('Remote end closed connection without response'))",
+            "[youtube] dQw4w9WgXcQ: Unable to download webpage: [Errno 104] Connection reset by peer",
+        ];
+        for reason in transient {
+            assert!(
+                YtdlError::Failed(reason.to_string()).is_transient(),
+                "{reason}"
+            );
+        }
+
+        let lasting = [
+            "[generic] country-roads: Unable to download webpage: HTTP Error 404: Not Found (caused by <HTTPError 404: Not Found>)",
+            "[youtube] dQw4w9WgXcQ: Unable to download webpage: HTTP Error 403: Forbidden",
+            "[youtube] dQw4w9WgXcQ: Unable to extract uploader id",
+            "[youtube] dQw4w9WgXcQ: Requested format is not available. Use --list-formats for a list of available formats",
+            "Unsupported URL: https://example.com/",
+        ];
+        for reason in lasting {
+            assert!(
+                !YtdlError::Failed(reason.to_string()).is_transient(),
+                "{reason}"
+            );
+        }
+
+        assert!(YtdlError::Timeout.is_transient());
+        assert!(YtdlError::Io(io::Error::other("spawn failed")).is_transient());
+        assert!(!YtdlError::NotInstalled.is_transient());
+        assert!(!YtdlError::Unavailable.is_transient());
+        assert!(!YtdlError::AgeRestricted.is_transient());
+        assert!(!YtdlError::GeoBlocked.is_transient());
+    }
+
+    #[test]
+    fn keeps_the_error_message() {
+        let stderr = "ERROR: [youtube] dQw4w9WgXcQ: Unable to extract uploader id\n";
+        match classify(stderr) {
+            YtdlError::Failed(reason) => {
+                assert_eq!(
+                    reason,
+                    "[youtube] dQw4w9WgXcQ: Unable to extract uploader id"
+                )
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+}